@@ -29,13 +29,20 @@ use rari_doc::search_index::build_search_index;
 use rari_doc::utils::TEMPL_RECORDER_SENDER;
 use rari_sitemap::Sitemaps;
 use rari_tools::add_redirect::add_redirect;
+use rari_tools::check_links::check_links;
+use rari_tools::confirm::{AlwaysYes, Confirmer, TerminalConfirmer};
 use rari_tools::fix::fixer::fix_all;
 use rari_tools::history::gather_history;
 use rari_tools::inventory::gather_inventory;
-use rari_tools::r#move::r#move;
-use rari_tools::redirects::{fix_redirects, validate_redirects};
+use rari_tools::preview::preview_slug;
+use rari_tools::r#move::{r#move, MoveOptions};
+use rari_tools::redirects::{
+    fix_redirects, import_redirects, rebuild_redirects, repair_redirects, validate_redirects,
+};
 use rari_tools::remove::remove;
 use rari_tools::sidebars::{fmt_sidebars, sync_sidebars};
+use rari_tools::slug_consistency::{find_duplicate_slugs, verify_slug_consistency};
+use rari_tools::slugify::slugify_preview;
 use rari_tools::sync_translated_content::sync_translated_content;
 use rari_types::globals::{build_out_root, content_root, content_translated_root, SETTINGS};
 use rari_types::locale::Locale;
@@ -45,7 +52,7 @@ use schemars::schema_for;
 use self_update::cargo_crate_version;
 use tabwriter::TabWriter;
 use tracing::level_filters::LevelFilter;
-use tracing::{info, Level};
+use tracing::{error, info, warn, Level};
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::{filter, Layer};
@@ -115,10 +122,30 @@ enum ContentSubcommand {
     FixRedirects(FixRedirectArgs),
     /// Validate redirects.
     ValidateRedirects(ValidateRedirectArgs),
+    /// Repair redirects pointing to pages that do not exist anymore.
+    ///
+    /// Dangling redirects are removed, or repointed to the fallback URL.
+    RepairRedirects(RepairRedirectsArgs),
+    /// Rebuild the redirects file from scratch.
+    ///
+    /// Removes duplicate, cyclic and dangling redirects, flattens chains and
+    /// sorts the file.
+    RebuildRedirects(RebuildRedirectsArgs),
+    /// Import redirects from a two-column (tab or comma separated) file.
+    ImportRedirects(ImportRedirectsArgs),
     /// Create content inventory as JSON
     Inventory,
+    /// Verify that the slug of each doc matches its folder, and that no two
+    /// docs share a slug.
+    VerifySlugs(VerifySlugsArgs),
+    /// Check that the internal links of all docs lead to a page.
+    CheckLinks(CheckLinksArgs),
+    /// Show the slug, URL and folder a title would produce.
+    Slugify(SlugifyArgs),
     /// Fix all flaws (currently only broken_links)
     FixFlaws(FixFlawsArgs),
+    /// Print the HTML the markdown of a doc renders to, without macros.
+    Preview(PreviewArgs),
 }
 
 #[derive(Args)]
@@ -128,6 +155,27 @@ struct MoveArgs {
     locale: Option<Locale>,
     #[arg(short = 'y', long, help = "Assume yes to all prompts")]
     assume_yes: bool,
+    #[arg(
+        long,
+        help = "Skip subpages that already exist at the destination and move the rest"
+    )]
+    merge: bool,
+    #[arg(long, help = "Also move existing translations of the moved documents")]
+    all_locales: bool,
+    #[arg(long, help = "Refuse to move documents to another top-level section")]
+    restrict_top_level: bool,
+    #[arg(long, help = "Update titles derived from the old slug to the new slug")]
+    update_title: bool,
+    #[arg(
+        long,
+        help = "Also record the old slugs as aliases of the moved documents"
+    )]
+    alias: bool,
+    #[arg(
+        long,
+        help = "URL segment the docs are served under, for the redirects (default: docs)"
+    )]
+    redirect_prefix: Option<String>,
 }
 
 #[derive(Args)]
@@ -153,6 +201,51 @@ struct ValidateRedirectArgs {
     locales: Option<Vec<Locale>>,
 }
 
+#[derive(Args)]
+struct RepairRedirectsArgs {
+    locale: Option<Locale>,
+    #[arg(
+        long,
+        help = "URL to redirect to instead of removing dangling redirects"
+    )]
+    fallback: Option<String>,
+}
+
+#[derive(Args)]
+struct RebuildRedirectsArgs {
+    locale: Option<Locale>,
+}
+
+#[derive(Args)]
+struct ImportRedirectsArgs {
+    file: PathBuf,
+    locale: Option<Locale>,
+}
+
+#[derive(Args)]
+struct VerifySlugsArgs {
+    locale: Option<Locale>,
+}
+
+#[derive(Args)]
+struct CheckLinksArgs {
+    locale: Option<Locale>,
+}
+
+#[derive(Args)]
+struct SlugifyArgs {
+    title: String,
+    #[arg(long, help = "Slug of the parent doc")]
+    parent: Option<String>,
+    locale: Option<Locale>,
+}
+
+#[derive(Args)]
+struct PreviewArgs {
+    slug: String,
+    locale: Option<Locale>,
+}
+
 #[derive(Args)]
 struct FixRedirectArgs {
     locales: Option<Vec<Locale>>,
@@ -485,7 +578,25 @@ fn main() -> Result<(), Error> {
         }
         Commands::Content(content_subcommand) => match content_subcommand {
             ContentSubcommand::Move(args) => {
-                r#move(&args.old_slug, &args.new_slug, args.locale, args.assume_yes)?;
+                let confirmer: &dyn Confirmer = if args.assume_yes {
+                    &AlwaysYes
+                } else {
+                    &TerminalConfirmer
+                };
+                r#move(
+                    &args.old_slug,
+                    &args.new_slug,
+                    args.locale,
+                    &MoveOptions {
+                        merge: args.merge,
+                        all_locales: args.all_locales,
+                        restrict_top_level: args.restrict_top_level,
+                        update_title: args.update_title,
+                        alias: args.alias,
+                        redirect_prefix: args.redirect_prefix,
+                    },
+                    confirmer,
+                )?;
             }
             ContentSubcommand::Delete(args) => {
                 remove(
@@ -515,9 +626,95 @@ fn main() -> Result<(), Error> {
             ContentSubcommand::ValidateRedirects(args) => {
                 validate_redirects(args.locales.as_deref())?;
             }
+            ContentSubcommand::RepairRedirects(args) => {
+                let repairs =
+                    repair_redirects(args.locale.unwrap_or_default(), args.fallback.as_deref())?;
+                for repair in &repairs {
+                    match &repair.new_to {
+                        Some(new_to) => {
+                            info!("{} -> {} (was {})", repair.from, new_to, repair.to)
+                        }
+                        None => info!("removed {} -> {}", repair.from, repair.to),
+                    }
+                }
+            }
+            ContentSubcommand::RebuildRedirects(args) => {
+                let report = rebuild_redirects(args.locale.unwrap_or_default())?;
+                info!(
+                    "kept {} redirects ({} flattened), removed {} ({} duplicate, {} cyclic, {} dangling)",
+                    report.kept,
+                    report.flattened,
+                    report.removed(),
+                    report.duplicates,
+                    report.cyclic,
+                    report.dangling
+                );
+            }
+            ContentSubcommand::ImportRedirects(args) => {
+                let rejected = import_redirects(args.locale.unwrap_or_default(), &args.file)?;
+                for rejected in &rejected {
+                    warn!(
+                        "line {}: {} ({})",
+                        rejected.line, rejected.row, rejected.reason
+                    );
+                }
+            }
             ContentSubcommand::Inventory => {
                 gather_inventory()?;
             }
+            ContentSubcommand::VerifySlugs(args) => {
+                let locale = args.locale.unwrap_or_default();
+                let mismatches = verify_slug_consistency(locale)?;
+                for mismatch in &mismatches {
+                    error!(
+                        "{} is in {} instead of {}",
+                        mismatch.slug,
+                        mismatch.folder.display(),
+                        mismatch.expected_folder.display()
+                    );
+                }
+                let duplicates = find_duplicate_slugs(locale)?;
+                for (slug, files) in &duplicates {
+                    error!(
+                        "{slug} is the slug of {}",
+                        files
+                            .iter()
+                            .map(|file| file.display().to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+                if !mismatches.is_empty() {
+                    return Err(anyhow!("{} docs with inconsistent slugs", mismatches.len()));
+                }
+                if !duplicates.is_empty() {
+                    return Err(anyhow!("{} duplicate slugs", duplicates.len()));
+                }
+            }
+            ContentSubcommand::CheckLinks(args) => {
+                let report = check_links(args.locale.unwrap_or_default())?;
+                for link in &report.broken {
+                    error!("{}:{} links to {}", link.slug, link.line, link.url);
+                }
+                info!("checked {} links", report.checked);
+                if !report.broken.is_empty() {
+                    return Err(anyhow!("{} broken links", report.broken.len()));
+                }
+            }
+            ContentSubcommand::Slugify(args) => {
+                let preview = slugify_preview(
+                    &args.title,
+                    args.parent.as_deref(),
+                    args.locale.unwrap_or_default(),
+                )?;
+                info!("segment: {}", preview.segment);
+                info!("slug:    {}", preview.slug);
+                info!("url:     {}", preview.url);
+                info!("folder:  {}", preview.folder.display());
+            }
+            ContentSubcommand::Preview(args) => {
+                preview_slug(&args.slug, args.locale.unwrap_or_default())?;
+            }
             ContentSubcommand::FixFlaws(args) => {
                 let start = std::time::Instant::now();
                 let mut settings = Settings::new()?;
@@ -0,0 +1,34 @@
+use rari_md::body_word_count;
+
+use crate::pages::page::PageLike;
+
+/// Pages with fewer words in their body are stubs.
+pub const STUB_WORD_THRESHOLD: usize = 50;
+
+/// Returns `true` if the page is a stub, i.e. its body has fewer than
+/// [`STUB_WORD_THRESHOLD`] words of plain text. Headings are not counted, so a
+/// page with only front matter and headings is always a stub.
+pub fn is_stub(page: &impl PageLike) -> bool {
+    is_stub_content(page.content())
+}
+
+fn is_stub_content(content: &str) -> bool {
+    body_word_count(content) < STUB_WORD_THRESHOLD
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_stub_content() {
+        assert!(is_stub_content("## Syntax\n"));
+        assert!(is_stub_content(
+            "{{APIRef}}\n\nThe **`foo()`** method does foo.\n\n## Syntax\n"
+        ));
+        let description = ["The method does foo to the bar."; 10].join(" ");
+        assert!(!is_stub_content(&format!(
+            "{{{{APIRef}}}}\n\n{description}\n\n## Syntax\n"
+        )));
+    }
+}
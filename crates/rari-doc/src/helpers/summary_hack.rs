@@ -29,7 +29,10 @@ pub fn get_hacky_summary_md(page: &Page) -> Result<String, DocError> {
             Ok(m2h_internal(
                 md.trim(),
                 page.locale(),
-                M2HOptions { sourcepos: false },
+                M2HOptions {
+                    sourcepos: false,
+                    ..Default::default()
+                },
             )?)
         })
     }
@@ -73,6 +73,9 @@ pub struct FrontMatter {
     pub spec_urls: Vec<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub original_slug: Option<String>,
+    /// Earlier slugs of the doc, kept when it was moved.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
     #[serde(
         deserialize_with = "t_or_vec",
         serialize_with = "serialize_t_or_vec",
@@ -95,6 +98,7 @@ pub struct Meta {
     pub browser_compat: Vec<String>,
     pub spec_urls: Vec<String>,
     pub original_slug: Option<String>,
+    pub aliases: Vec<String>,
     pub sidebar: Vec<String>,
     pub locale: Locale,
     pub full_path: PathBuf,
@@ -305,6 +309,7 @@ fn read_doc(path: impl Into<PathBuf>) -> Result<Doc, DocError> {
         browser_compat,
         spec_urls,
         original_slug,
+        aliases,
         sidebar,
         ..
     } = serde_yaml_ng::from_str(fm)?;
@@ -346,6 +351,7 @@ fn read_doc(path: impl Into<PathBuf>) -> Result<Doc, DocError> {
             browser_compat,
             spec_urls,
             original_slug,
+            aliases,
             sidebar,
             locale,
             full_path,
@@ -379,6 +385,7 @@ fn write_doc(doc: &Doc) -> Result<(), DocError> {
         browser_compat: doc.meta.browser_compat.clone(),
         spec_urls: doc.meta.spec_urls.clone(),
         original_slug: doc.meta.original_slug.clone(),
+        aliases: doc.meta.aliases.clone(),
         sidebar: doc.meta.sidebar.clone(),
         ..frontmatter
     };
@@ -0,0 +1,76 @@
+/// Whether `c` belongs to a right-to-left script (Hebrew, Arabic, Syriac,
+/// Thaana, N'Ko, Samaritan, Mandaic and their presentation forms).
+fn is_rtl(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+    )
+}
+
+/// Whether `c` has a strong left-to-right direction.
+fn is_ltr(c: char) -> bool {
+    c.is_alphabetic() && !is_rtl(c)
+}
+
+/// Splits `text` into runs, flagging the right-to-left ones.
+///
+/// A right-to-left run starts and ends with a right-to-left character and
+/// spans everything in between that is not a left-to-right letter, so
+/// spaces and punctuation within a phrase stay inside. Text without any
+/// right-to-left character is a single left-to-right run.
+pub(crate) fn split_rtl_runs(text: &str) -> Vec<(&str, bool)> {
+    let mut runs = vec![];
+    let mut start = 0;
+    let mut rtl_start = None;
+    let mut rtl_end = 0;
+    for (i, c) in text.char_indices() {
+        if is_rtl(c) {
+            if rtl_start.is_none() {
+                rtl_start = Some(i);
+            }
+            rtl_end = i + c.len_utf8();
+        } else if is_ltr(c) {
+            if let Some(rtl) = rtl_start.take() {
+                if start < rtl {
+                    runs.push((&text[start..rtl], false));
+                }
+                runs.push((&text[rtl..rtl_end], true));
+                start = rtl_end;
+            }
+        }
+    }
+    if let Some(rtl) = rtl_start {
+        if start < rtl {
+            runs.push((&text[start..rtl], false));
+        }
+        runs.push((&text[rtl..rtl_end], true));
+        start = rtl_end;
+    }
+    if start < text.len() {
+        runs.push((&text[start..], false));
+    }
+    runs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_rtl_runs() {
+        assert_eq!(split_rtl_runs("plain text"), vec![("plain text", false)]);
+        assert_eq!(
+            split_rtl_runs("say مرحبا بالعالم, then go"),
+            vec![
+                ("say ", false),
+                ("مرحبا بالعالم", true),
+                (", then go", false)
+            ]
+        );
+        assert_eq!(split_rtl_runs("שלום"), vec![("שלום", true)]);
+    }
+}
@@ -0,0 +1,28 @@
+use comrak::nodes::Sourcepos;
+
+/// The kind of issue a [`RenderDiagnostic`] reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderDiagnosticKind {
+    /// Raw HTML that was not allowed and got replaced by a placeholder.
+    RawHtml,
+    /// Nodes nested deeper than the configured maximum depth, which were not rendered.
+    MaxDepthExceeded,
+    /// Front matter that could not be parsed and was skipped.
+    InvalidFrontMatter,
+    /// A heading that still contains a template placeholder, so it got no id.
+    TemplatedHeading,
+    /// A heading more than one level deeper than the heading before it.
+    SkippedHeadingLevel,
+    /// A code block whose language is not one of the known languages.
+    UnknownLanguage,
+}
+
+/// A recoverable issue found while rendering markdown to HTML.
+///
+/// Diagnostics never abort rendering, the output stays valid HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderDiagnostic {
+    pub kind: RenderDiagnosticKind,
+    pub sourcepos: Sourcepos,
+    pub message: String,
+}
@@ -1,3 +1,6 @@
+use std::fmt;
+use std::io;
+
 use rari_types::ArgError;
 use thiserror::Error;
 
@@ -34,3 +37,49 @@ pub enum MarkdownError {
     #[error(transparent)]
     DocError(#[from] DocError),
 }
+
+/// The limit of [`crate::RenderLimits`] that was crossed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderLimit {
+    Nodes,
+    OutputBytes,
+}
+
+impl fmt::Display for RenderLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Nodes => f.write_str("max nodes"),
+            Self::OutputBytes => f.write_str("max output bytes"),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum RenderError {
+    #[error("render limit exceeded: {0}")]
+    LimitExceeded(RenderLimit),
+    #[error(transparent)]
+    IOError(io::Error),
+}
+
+impl From<RenderError> for io::Error {
+    fn from(e: RenderError) -> Self {
+        match e {
+            RenderError::IOError(e) => e,
+            e => io::Error::other(e),
+        }
+    }
+}
+
+/// Recovers a [`RenderError`] passed through `io` as the inner error.
+impl From<io::Error> for RenderError {
+    fn from(e: io::Error) -> Self {
+        if !e.get_ref().is_some_and(|inner| inner.is::<RenderError>()) {
+            return RenderError::IOError(e);
+        }
+        match e.into_inner().map(|inner| inner.downcast::<RenderError>()) {
+            Some(Ok(e)) => *e,
+            _ => unreachable!("checked above"),
+        }
+    }
+}
@@ -1,5 +1,16 @@
+use comrak::nodes::{AstNode, LineColumn, NodeValue, Sourcepos};
+use comrak::Arena;
+
 pub(crate) enum Flag {
     Card,
+    /// A block quote citing this URL, written in its footer.
+    Citation(String),
+    /// The node rendered its own content, its children are not visited.
+    SkipChildren,
+    /// A link rendered as its plain content, without `<a>`.
+    Unlinked,
+    /// A link showing its own URL, like autolinks.
+    Autolink,
     None,
 }
 
@@ -7,3 +18,203 @@ pub static DELIM_START: &str = "⟬";
 pub static DELIM_START_LEN: usize = DELIM_START.len();
 pub static DELIM_END: &str = "⟭";
 pub static DELIM_END_LEN: usize = DELIM_END.len();
+
+/// The literal of the `Raw` nodes holding `==highlighted==` inlines, which
+/// are rendered as `<mark>`. comrak has no node for highlights.
+pub static HIGHLIGHT: &str = "==";
+
+/// Moves the `==highlighted==` inlines of every node below `root` into a
+/// `Raw` node with the literal [`HIGHLIGHT`].
+///
+/// A highlight opens with `==` followed by a non-space character and closes
+/// at the next `==` preceded by a non-space character. Both delimiters must
+/// have the same parent, so `==a *b*==` is a highlight but `*==a*==` is not.
+/// Runs of three or more `=` and unbalanced delimiters are left as text.
+pub(crate) fn wrap_highlights<'a>(arena: &'a Arena<AstNode<'a>>, root: &'a AstNode<'a>) {
+    let parents = root
+        .descendants()
+        .filter(|node| node.children().any(is_text))
+        .collect::<Vec<_>>();
+    for parent in parents {
+        let mut next = parent.first_child();
+        while let Some(node) = next {
+            let Some(open) = find_delimiter(node, 0, true) else {
+                next = node.next_sibling();
+                continue;
+            };
+            let mut close = None;
+            let (mut candidate, mut from) = (Some(node), open + 2);
+            while let Some(node) = candidate {
+                if let Some(at) = find_delimiter(node, from, false) {
+                    close = Some((node, at));
+                    break;
+                }
+                (candidate, from) = (node.next_sibling(), 0);
+            }
+            let Some((close_node, close)) = close else {
+                break;
+            };
+            next = wrap_highlight(arena, node, open, close_node, close).next_sibling();
+        }
+    }
+}
+
+fn is_text<'a>(node: &'a AstNode<'a>) -> bool {
+    matches!(node.data.borrow().value, NodeValue::Text(_))
+}
+
+/// Returns the literal of a text node.
+fn text_of<'a>(node: &'a AstNode<'a>) -> Option<String> {
+    match node.data.borrow().value {
+        NodeValue::Text(ref literal) => Some(literal.clone()),
+        _ => None,
+    }
+}
+
+/// Finds the first opening or closing `==` at or behind `from` in the text
+/// `node`. Delimiters at the edges of the text look at the siblings instead.
+fn find_delimiter<'a>(node: &'a AstNode<'a>, from: usize, opening: bool) -> Option<usize> {
+    let text = text_of(node)?;
+    let is_space = |sibling: Option<&'a AstNode<'a>>| {
+        sibling.map_or(true, |sibling| {
+            matches!(
+                sibling.data.borrow().value,
+                NodeValue::SoftBreak | NodeValue::LineBreak
+            )
+        })
+    };
+    let mut search = from;
+    while let Some(at) = text[search..].find("==").map(|i| i + search) {
+        let end = at + 2 + text[at + 2..].len() - text[at + 2..].trim_start_matches('=').len();
+        search = end;
+        if end - at != 2 || text[..at].ends_with('=') {
+            continue;
+        }
+        let found = if opening {
+            match text[end..].chars().next() {
+                Some(c) => !c.is_whitespace(),
+                None => !is_space(node.next_sibling()),
+            }
+        } else {
+            match text[..at].chars().next_back() {
+                Some(c) => !c.is_whitespace(),
+                None => !is_space(node.previous_sibling()),
+            }
+        };
+        if found {
+            return Some(at);
+        }
+    }
+    None
+}
+
+/// Moves everything between the `==` at `open` in the text `open_node` and
+/// the one at `close` in its sibling `close_node` into a new highlight node,
+/// dropping the delimiters. Returns the highlight node.
+fn wrap_highlight<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    open_node: &'a AstNode<'a>,
+    open: usize,
+    close_node: &'a AstNode<'a>,
+    close: usize,
+) -> &'a AstNode<'a> {
+    let start = column_at(open_node, open);
+    let end = column_at(close_node, close + 1);
+    split_text(arena, close_node, close, close + 2);
+    let inner = split_text(arena, open_node, open, open + 2);
+    let last = if open_node.same_node(close_node) {
+        inner
+    } else {
+        Some(close_node)
+    };
+
+    let highlight = arena.alloc(NodeValue::Raw(HIGHLIGHT.to_string()).into());
+    if let (Some(start), Some(end)) = (start, end) {
+        highlight.data.borrow_mut().sourcepos = Sourcepos { start, end };
+    }
+    open_node.insert_after(highlight);
+    while let Some(node) = highlight.next_sibling() {
+        highlight.append(node);
+        if last.is_some_and(|last| last.same_node(node)) {
+            break;
+        }
+    }
+    for node in [open_node, close_node] {
+        if matches!(node.data.borrow().value, NodeValue::Text(ref text) if text.is_empty()) {
+            node.detach();
+        }
+    }
+    highlight
+}
+
+/// Cuts the bytes `start..end` out of the text `node`. The text behind them
+/// goes into a new text node following `node`, which is returned.
+fn split_text<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    node: &'a AstNode<'a>,
+    start: usize,
+    end: usize,
+) -> Option<&'a AstNode<'a>> {
+    let text = text_of(node)?;
+    let head_end = start.checked_sub(1).and_then(|last| column_at(node, last));
+    let tail_start = column_at(node, end);
+    let tail_end = node.data.borrow().sourcepos.end;
+    if let NodeValue::Text(ref mut literal) = node.data.borrow_mut().value {
+        literal.truncate(start);
+    }
+    if let Some(column) = head_end {
+        node.data.borrow_mut().sourcepos.end = column;
+    }
+    if end == text.len() {
+        return None;
+    }
+    let tail = arena.alloc(NodeValue::Text(text[end..].to_string()).into());
+    if let Some(start) = tail_start {
+        tail.data.borrow_mut().sourcepos = Sourcepos {
+            start,
+            end: tail_end,
+        };
+    }
+    node.insert_after(tail);
+    Some(tail)
+}
+
+/// The position of the byte `offset` of the text `node`. Only known if the
+/// node is a single line of unescaped source, otherwise the columns cannot be
+/// derived from the literal.
+fn column_at<'a>(node: &'a AstNode<'a>, offset: usize) -> Option<LineColumn> {
+    let ast = node.data.borrow();
+    let (start, end) = (ast.sourcepos.start, ast.sourcepos.end);
+    let NodeValue::Text(ref literal) = ast.value else {
+        return None;
+    };
+    (start.line > 0 && start.line == end.line && end.column + 1 - start.column == literal.len())
+        .then(|| LineColumn {
+            line: start.line,
+            column: start.column + offset,
+        })
+}
+
+/// How a collapsible alert (`[!NOTE]-` or `[!NOTE]+`) starts out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Collapsible {
+    Closed,
+    Open,
+}
+
+/// Splits the collapsible marker off an alert title. comrak keeps everything
+/// after `[!NOTE]` as the title, so `[!NOTE]- Title` has the title `- Title`.
+pub(crate) fn alert_collapsible(title: Option<&str>) -> (Option<Collapsible>, Option<&str>) {
+    let Some(title) = title else {
+        return (None, None);
+    };
+    let (collapsible, rest) = if let Some(rest) = title.strip_prefix('-') {
+        (Collapsible::Closed, rest)
+    } else if let Some(rest) = title.strip_prefix('+') {
+        (Collapsible::Open, rest)
+    } else {
+        return (None, Some(title));
+    };
+    let rest = rest.trim();
+    (Some(collapsible), (!rest.is_empty()).then_some(rest))
+}
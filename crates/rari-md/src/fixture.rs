@@ -0,0 +1,146 @@
+//! Golden-file tests: markdown fixtures rendered and compared against the
+//! committed HTML next to them.
+//!
+//! A fixture is a `name.md` file, its expectation is `name.html` in the same
+//! directory. Run the fixture tests with `RARI_UPDATE_FIXTURES=1` to
+//! (re)write the expectations from the current output.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use comrak::Arena;
+use rari_types::locale::Locale;
+
+use crate::error::MarkdownError;
+use crate::html::format_document;
+use crate::{comrak_options, parse, M2HOptions};
+
+/// Renders the markdown fixture at `path`.
+///
+/// Unlike [`crate::m2h_internal`] footnotes and math are enabled, so the
+/// fixtures can cover every node the formatter knows about.
+pub fn render_fixture(path: &Path, options: &M2HOptions) -> Result<String, MarkdownError> {
+    let input = fs::read_to_string(path)?;
+    let arena = Arena::new();
+    let mut comrak_options = comrak_options(options);
+    comrak_options.extension.footnotes = true;
+    comrak_options.extension.math_dollars = true;
+    comrak_options.extension.math_code = true;
+    let root = parse(&arena, &input, &comrak_options, options);
+    let mut html = vec![];
+    format_document(root, &comrak_options, options, &mut html, Locale::EnUs)
+        .map_err(|_| MarkdownError::HTMLFormatError)?;
+    String::from_utf8(html).map_err(|_| MarkdownError::HTMLFormatError)
+}
+
+/// A fixture whose rendering differs from its expectation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixtureMismatch {
+    pub path: PathBuf,
+    /// The committed expectation, `None` if there is none yet.
+    pub expected: Option<String>,
+    pub actual: String,
+}
+
+impl fmt::Display for FixtureMismatch {
+    /// Shows the differing lines, `-` for the expectation and `+` for the
+    /// actual output.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Some(expected) = &self.expected else {
+            return writeln!(f, "{}: no expectation", self.path.display());
+        };
+        writeln!(f, "{}:", self.path.display())?;
+        let mut expected_lines = expected.lines();
+        let mut actual_lines = self.actual.lines();
+        let mut line = 1;
+        loop {
+            match (expected_lines.next(), actual_lines.next()) {
+                (None, None) => break,
+                (e, a) if e == a => {}
+                (e, a) => {
+                    if let Some(e) = e {
+                        writeln!(f, "{line:>4} - {e}")?;
+                    }
+                    if let Some(a) = a {
+                        writeln!(f, "{line:>4} + {a}")?;
+                    }
+                }
+            }
+            line += 1;
+        }
+        Ok(())
+    }
+}
+
+/// Renders every `.md` fixture in `dir` and returns the ones that do not
+/// match their `.html` expectation, sorted by path. With `update` set the
+/// expectations are written instead and nothing is returned.
+pub fn check_fixtures(
+    dir: &Path,
+    options: &M2HOptions,
+    update: bool,
+) -> Result<Vec<FixtureMismatch>, MarkdownError> {
+    let mut fixtures = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    fixtures.retain(|path| path.extension().is_some_and(|ext| ext == "md"));
+    fixtures.sort();
+
+    let mut mismatches = vec![];
+    for path in fixtures {
+        let actual = render_fixture(&path, options)?;
+        let expected_path = path.with_extension("html");
+        if update {
+            fs::write(&expected_path, &actual)?;
+            continue;
+        }
+        let expected = fs::read_to_string(&expected_path).ok();
+        if expected.as_deref() != Some(actual.as_str()) {
+            mismatches.push(FixtureMismatch {
+                path,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fixtures() -> Result<(), anyhow::Error> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures");
+        let options = M2HOptions {
+            sourcepos: false,
+            ..Default::default()
+        };
+        let update = std::env::var_os("RARI_UPDATE_FIXTURES").is_some();
+        let mismatches = check_fixtures(&dir, &options, update)?;
+        assert!(
+            mismatches.is_empty(),
+            "{}",
+            mismatches
+                .iter()
+                .map(ToString::to_string)
+                .collect::<String>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fixture_mismatch_display() {
+        let mismatch = FixtureMismatch {
+            path: PathBuf::from("a.md"),
+            expected: Some("<p>a</p>\n<p>b</p>\n".to_string()),
+            actual: "<p>a</p>\n<p>c</p>\n<hr />\n".to_string(),
+        };
+        assert_eq!(
+            mismatch.to_string(),
+            "a.md:\n   2 - <p>b</p>\n   2 + <p>c</p>\n   3 + <hr />\n"
+        );
+    }
+}
@@ -6,58 +6,543 @@
 //! The HTML renderer for the CommonMark AST, as well as helper functions.
 use std::borrow::Cow;
 use std::cell::Cell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::io::{self, Write};
 use std::str;
+use std::sync::LazyLock;
 
 use comrak::adapters::HeadingMeta;
 use comrak::nodes::{
-    AstNode, ListType, NodeCode, NodeFootnoteDefinition, NodeMath, NodeTable, NodeValue,
+    AstNode, ListType, NodeCode, NodeFootnoteDefinition, NodeMath, NodeTable, NodeValue, Sourcepos,
     TableAlignment,
 };
-use comrak::{ComrakOptions, ComrakPlugins, Options, Plugins};
+use comrak::{Arena, ComrakOptions, ComrakPlugins, Options, Plugins};
 use itertools::Itertools;
 use rari_types::locale::Locale;
+use regex::Regex;
 
 use crate::anchor;
+use crate::bidi::split_rtl_runs;
 use crate::character_set::character_set;
 use crate::ctype::isspace;
-use crate::ext::{Flag, DELIM_START};
-use crate::node_card::{alert_type_css_class, alert_type_default_title, is_callout, NoteCard};
+use crate::diagnostic::{RenderDiagnostic, RenderDiagnosticKind};
+use crate::error::{RenderError, RenderLimit};
+use crate::ext::{alert_collapsible, Collapsible, Flag, DELIM_START, HIGHLIGHT};
+use crate::node_card::{
+    alert_type_css_class, alert_type_default_title, is_callout, take_caption, take_citation,
+    NoteCard,
+};
+use crate::sanitize::sanitize_html;
+use crate::smart::smarten;
+use crate::{EmptyHeadingIds, M2HOptions, SpoilerAttr};
 
 /// Formats an AST as HTML, modified by the given options.
 pub fn format_document<'a>(
     root: &'a AstNode<'a>,
     options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+    output: &mut dyn Write,
+    locale: Locale,
+) -> io::Result<()> {
+    format_document_with_plugins(
+        root,
+        options,
+        m2h_options,
+        output,
+        &ComrakPlugins::default(),
+        locale,
+    )
+    .map(|_| ())
+}
+
+/// Formats the subtree rooted at `node` as HTML, modified by the given options.
+///
+/// The subtree is rendered with a fresh [`Anchorizer`] and its own footnote
+/// state, so footnotes defined in the subtree are closed at its end.
+pub fn format_node_subtree<'a>(
+    node: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
     output: &mut dyn Write,
     locale: Locale,
 ) -> io::Result<()> {
-    format_document_with_plugins(root, options, output, &ComrakPlugins::default(), locale)
+    format_document_with_plugins(
+        node,
+        options,
+        m2h_options,
+        output,
+        &ComrakPlugins::default(),
+        locale,
+    )
+    .map(|_| ())
+}
+
+/// Formats an AST as HTML, modified by the given options. Returns the collected diagnostics.
+pub fn format_document_with_diagnostics<'a>(
+    root: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+    output: &mut dyn Write,
+    locale: Locale,
+) -> io::Result<Vec<RenderDiagnostic>> {
+    format_document_with_plugins(
+        root,
+        options,
+        m2h_options,
+        output,
+        &ComrakPlugins::default(),
+        locale,
+    )
 }
 
 /// Formats an AST as HTML, modified by the given options. Accepts custom plugins.
 pub fn format_document_with_plugins<'a>(
     root: &'a AstNode<'a>,
     options: &ComrakOptions,
+    m2h_options: &M2HOptions,
     output: &mut dyn Write,
     plugins: &ComrakPlugins,
     locale: Locale,
-) -> io::Result<()> {
+) -> io::Result<Vec<RenderDiagnostic>> {
+    format_document_internal(
+        root,
+        options,
+        m2h_options,
+        output,
+        plugins,
+        locale,
+        &RenderLimits::default(),
+    )
+    .map(|rendered| rendered.diagnostics)
+}
+
+/// Limits for rendering untrusted input. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderLimits {
+    /// Maximum number of AST nodes to render.
+    pub max_nodes: Option<usize>,
+    /// Maximum number of bytes to write to the output.
+    pub max_output_bytes: Option<usize>,
+}
+
+/// Formats an AST as HTML, modified by the given options, within `limits`.
+///
+/// Rendering stops with [`RenderError::LimitExceeded`] as soon as a limit is
+/// crossed. Whatever was rendered up to then has been written to `output`, it
+/// is not valid HTML in general.
+pub fn format_document_limited<'a>(
+    root: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+    output: &mut dyn Write,
+    locale: Locale,
+    limits: &RenderLimits,
+) -> Result<Vec<RenderDiagnostic>, RenderError> {
+    format_document_internal(
+        root,
+        options,
+        m2h_options,
+        output,
+        &ComrakPlugins::default(),
+        locale,
+        limits,
+    )
+    .map(|rendered| rendered.diagnostics)
+    .map_err(RenderError::from)
+}
+
+/// Formats an AST as HTML, modified by the given options. Returns every
+/// emitted heading id, mapped to the source position of its heading.
+pub fn format_document_with_anchors<'a>(
+    root: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+    output: &mut dyn Write,
+    locale: Locale,
+) -> io::Result<HashMap<String, Sourcepos>> {
+    format_document_internal(
+        root,
+        options,
+        m2h_options,
+        output,
+        &ComrakPlugins::default(),
+        locale,
+        &RenderLimits::default(),
+    )
+    .map(|rendered| rendered.anchors)
+}
+
+/// Formats an AST as HTML, modified by the given options. Returns the
+/// distinct external origins (`https://example.com`) of images and embedded
+/// resources, in document order, if
+/// [`emit_preconnect_hints`](M2HOptions::emit_preconnect_hints) is set.
+pub fn format_document_with_external_origins<'a>(
+    root: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+    output: &mut dyn Write,
+    locale: Locale,
+) -> io::Result<Vec<String>> {
+    format_document_internal(
+        root,
+        options,
+        m2h_options,
+        output,
+        &ComrakPlugins::default(),
+        locale,
+        &RenderLimits::default(),
+    )
+    .map(|rendered| rendered.external_origins)
+}
+
+/// What is collected while rendering besides the HTML.
+struct Rendered {
+    diagnostics: Vec<RenderDiagnostic>,
+    anchors: HashMap<String, Sourcepos>,
+    external_origins: Vec<String>,
+}
+
+fn format_document_internal<'a>(
+    root: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+    output: &mut dyn Write,
+    plugins: &ComrakPlugins,
+    locale: Locale,
+    limits: &RenderLimits,
+) -> io::Result<Rendered> {
+    if !m2h_options.emit_preconnect_hints {
+        return format_document_body(root, options, m2h_options, output, plugins, locale, limits);
+    }
+    // The hints go in front of the HTML, so it is held back until all
+    // origins are known.
+    let mut html = vec![];
+    let rendered = format_document_body(
+        root,
+        options,
+        m2h_options,
+        &mut html,
+        plugins,
+        locale,
+        limits,
+    )?;
+    for origin in &rendered.external_origins {
+        output.write_all(b"<link rel=\"preconnect\" href=\"")?;
+        escape_href(output, origin.as_bytes())?;
+        output.write_all(b"\">\n")?;
+    }
+    output.write_all(&html)?;
+    Ok(rendered)
+}
+
+fn format_document_body<'a>(
+    root: &'a AstNode<'a>,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+    output: &mut dyn Write,
+    plugins: &ComrakPlugins,
+    locale: Locale,
+    limits: &RenderLimits,
+) -> io::Result<Rendered> {
     let mut writer = WriteWithLast {
         output,
         last_was_lf: Cell::new(true),
+        written: 0,
+        max_bytes: limits.max_output_bytes.unwrap_or(usize::MAX),
+        strip_newlines: m2h_options.no_newlines,
+        keep_newlines: false,
     };
-    let mut f = HtmlFormatter::new(options, &mut writer, plugins);
+    let mut f = HtmlFormatter::new(options, m2h_options, &mut writer, plugins);
+    f.max_nodes = limits.max_nodes.unwrap_or(usize::MAX);
+    if m2h_options.note_cards && m2h_options.table_captions {
+        f.table_captions = take_table_captions(root, locale);
+    }
+    if m2h_options.footnote_popover {
+        f.footnote_contents = collect_footnote_contents(root);
+    }
+    if m2h_options.reserve_html_ids {
+        for id in collect_html_ids(root) {
+            f.anchorizer.reserve(id);
+        }
+    }
+    // Pinned heading ids are kept as they are, generated ids avoid them.
+    if m2h_options.custom_heading_ids {
+        for id in collect_custom_heading_ids(root) {
+            f.anchorizer.reserve_pinned(id);
+        }
+    }
     f.format(root, false, locale)?;
     if f.footnote_ix > 0 {
         f.output.write_all(b"</ol>\n</section>\n")?;
     }
-    Ok(())
+    Ok(Rendered {
+        diagnostics: f.diagnostics,
+        anchors: f.anchors,
+        external_origins: f.external_origins,
+    })
+}
+
+/// Words per minute assumed by [`reading_time_minutes`].
+pub const READING_WORDS_PER_MINUTE: usize = 200;
+
+/// Inline code tokens from this length on get `<wbr>` break hints.
+const WBR_MIN_TOKEN_CHARS: usize = 20;
+
+/// Estimates the reading time of a document in minutes, rounded up, from the
+/// words of its plain text. Code blocks and raw HTML are not counted.
+pub fn reading_time_minutes<'a>(root: &'a AstNode<'a>) -> usize {
+    count_words(root, |value| {
+        matches!(
+            value,
+            NodeValue::Paragraph | NodeValue::Heading(_) | NodeValue::TableCell
+        )
+    })
+    .div_ceil(READING_WORDS_PER_MINUTE)
+}
+
+/// Counts the words of the plain text of a document's body, i.e. paragraphs
+/// and table cells. Headings, code blocks and raw HTML are not counted.
+pub fn count_body_words<'a>(root: &'a AstNode<'a>) -> usize {
+    count_words(root, |value| {
+        matches!(value, NodeValue::Paragraph | NodeValue::TableCell)
+    })
+}
+
+fn count_words<'a>(root: &'a AstNode<'a>, counted: impl Fn(&NodeValue) -> bool) -> usize {
+    let mut words = 0;
+    let mut text = Vec::with_capacity(256);
+    for node in root.descendants() {
+        if counted(&node.data.borrow().value) {
+            text.clear();
+            HtmlFormatter::collect_text(node, &mut text);
+            words += String::from_utf8_lossy(&text).split_whitespace().count();
+        }
+    }
+    words
+}
+
+/// The kind of a link found by [`collect_links`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Link,
+    /// A link whose text is its URL, `<https://…>` or a bare URL.
+    Autolink,
+    Image,
+    WikiLink,
+}
+
+/// A link of a document, with the URL as authored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinkInfo {
+    pub url: String,
+    /// The link text, or the alt text of an image.
+    pub text: String,
+    pub kind: LinkKind,
+    /// Bare URLs autolinked by the extension have no position of their own,
+    /// they get the one of the enclosing block.
+    pub sourcepos: Sourcepos,
+}
+
+/// Collects every link, image and wikilink of a document in document order.
+pub fn collect_links<'a>(root: &'a AstNode<'a>) -> Vec<LinkInfo> {
+    let mut links = vec![];
+    let mut text = Vec::with_capacity(64);
+    for node in root.descendants() {
+        let data = node.data.borrow();
+        let (url, kind) = match data.value {
+            NodeValue::Link(ref nl) => (&nl.url, LinkKind::Link),
+            NodeValue::Image(ref nl) => (&nl.url, LinkKind::Image),
+            NodeValue::WikiLink(ref nl) => (&nl.url, LinkKind::WikiLink),
+            _ => continue,
+        };
+        text.clear();
+        HtmlFormatter::collect_text(node, &mut text);
+        let text = String::from_utf8_lossy(&text).into_owned();
+        let kind = if kind == LinkKind::Link && text.as_bytes() == url.as_bytes() {
+            LinkKind::Autolink
+        } else {
+            kind
+        };
+        links.push(LinkInfo {
+            url: url.clone(),
+            text,
+            kind,
+            sourcepos: node
+                .ancestors()
+                .map(|node| node.data.borrow().sourcepos)
+                .find(|sourcepos| sourcepos.start.line > 0)
+                .unwrap_or(data.sourcepos),
+        });
+    }
+    links
+}
+
+static HEADING_ID: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[ \t]*\{#([A-Za-z][\w:.-]*)\}[ \t]*$").unwrap());
+
+/// Returns the id a heading pins with a trailing `{#id}`, removing the
+/// `{#id}` from the heading text if `strip` is set.
+fn custom_heading_id<'a>(heading: &'a AstNode<'a>, strip: bool) -> Option<String> {
+    let last = heading.last_child()?;
+    let mut data = last.data.borrow_mut();
+    let NodeValue::Text(ref mut text) = data.value else {
+        return None;
+    };
+    let cap = HEADING_ID.captures(text)?;
+    let id = cap[1].to_string();
+    if strip {
+        let start = cap.get(0).unwrap().start();
+        text.truncate(start);
+    }
+    Some(id)
+}
+
+static LIST_TYPE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[ \t]*\{type=([1aAiI])\}[ \t]*$").unwrap());
+
+/// Returns the numbering type an ordered list asks for with a trailing
+/// `{type=a}` on the first paragraph of its first item, and removes it.
+fn take_list_type<'a>(list: &'a AstNode<'a>) -> Option<String> {
+    let paragraph = list.first_child()?.first_child()?;
+    if !matches!(paragraph.data.borrow().value, NodeValue::Paragraph) {
+        return None;
+    }
+    let last = paragraph.last_child()?;
+    let mut data = last.data.borrow_mut();
+    let NodeValue::Text(ref mut text) = data.value else {
+        return None;
+    };
+    let cap = LIST_TYPE.captures(text)?;
+    let list_type = cap[1].to_string();
+    let start = cap.get(0).unwrap().start();
+    text.truncate(start);
+    Some(list_type)
+}
+
+static KEY_COMBO: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^(?:(?:Ctrl|Control|Alt|AltGr|Shift|Cmd|Command|Meta|Option|Opt|Win|Super|Fn)\s*\+\s*)+[^\s+]+$",
+    )
+    .unwrap()
+});
+
+/// Returns the keys of a table cell that is nothing but a key combination
+/// like `Ctrl+C` or `Cmd + Shift + Z`, starting with a modifier.
+fn key_combo<'a>(cell: &'a AstNode<'a>) -> Option<Vec<String>> {
+    let text = cell
+        .first_child()
+        .filter(|text| text.next_sibling().is_none())?;
+    let NodeValue::Text(ref literal) = text.data.borrow().value else {
+        return None;
+    };
+    let literal = literal.trim();
+    KEY_COMBO.is_match(literal).then(|| {
+        literal
+            .split('+')
+            .map(|key| key.trim().to_string())
+            .collect()
+    })
+}
+
+/// Collects the custom ids of all headings, see [`custom_heading_id`].
+fn collect_custom_heading_ids<'a>(root: &'a AstNode<'a>) -> Vec<String> {
+    root.descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::Heading(_)))
+        .filter_map(|heading| custom_heading_id(heading, false))
+        .collect()
+}
+
+/// Collects the values of `id` attributes in raw HTML.
+fn collect_html_ids<'a>(root: &'a AstNode<'a>) -> Vec<String> {
+    static HTML_ID: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"(?i)\sid\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+    let mut ids = vec![];
+    for node in root.descendants() {
+        let value = &node.data.borrow().value;
+        let literal = match value {
+            NodeValue::HtmlBlock(ref nhb) => &nhb.literal,
+            NodeValue::HtmlInline(ref literal) => literal,
+            _ => continue,
+        };
+        ids.extend(HTML_ID.captures_iter(literal).filter_map(|cap| {
+            cap.get(1)
+                .or_else(|| cap.get(2))
+                .map(|id| id.as_str().to_string())
+        }));
+    }
+    ids
+}
+
+/// Whether the link `text` is the `url` itself, as written for autolinks
+/// including `www.` ones, which get an `http://` scheme.
+fn shows_url(url: &str, text: &[u8]) -> bool {
+    let url = url.as_bytes();
+    url == text || url.strip_prefix(b"http://") == Some(text)
+}
+
+/// The origin of `url` if it is an absolute `http(s)` URL.
+fn external_origin(url: &str) -> Option<&str> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))?;
+    let host_len = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+    (host_len > 0).then(|| &url[..url.len() - rest.len() + host_len])
+}
+
+/// Takes the caption of every table from the block quote in front of it, see
+/// [`take_caption`]. Returns the positions of the tables with a caption.
+fn take_table_captions<'a>(root: &'a AstNode<'a>, locale: Locale) -> BTreeSet<Sourcepos> {
+    let tables = root
+        .descendants()
+        .filter(|node| matches!(node.data.borrow().value, NodeValue::Table(..)))
+        .collect::<Vec<_>>();
+    tables
+        .into_iter()
+        .filter(|table| {
+            table
+                .previous_sibling()
+                .filter(|prev| matches!(prev.data.borrow().value, NodeValue::BlockQuote))
+                .and_then(|prev| take_caption(prev, locale))
+                .is_some()
+        })
+        .map(|table| table.data.borrow().sourcepos)
+        .collect()
+}
+
+/// Collects the plain text of every footnote definition, keyed by its name.
+fn collect_footnote_contents<'a>(root: &'a AstNode<'a>) -> HashMap<String, String> {
+    root.descendants()
+        .filter_map(|node| match node.data.borrow().value {
+            NodeValue::FootnoteDefinition(ref nfd) => {
+                let mut text = Vec::with_capacity(20);
+                HtmlFormatter::collect_text(node, &mut text);
+                Some((
+                    nfd.name.clone(),
+                    String::from_utf8_lossy(&text).trim().to_string(),
+                ))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Returns `true` if `node` sits directly in a description list. The MDN
+/// definition list syntax yields terms and details as siblings, without a
+/// `DescriptionItem` around them.
+fn is_in_flat_dl<'a>(node: &'a AstNode<'a>) -> bool {
+    node.parent()
+        .is_some_and(|parent| matches!(parent.data.borrow().value, NodeValue::DescriptionList))
 }
 
 struct WriteWithLast<'w> {
     output: &'w mut dyn Write,
     last_was_lf: Cell<bool>,
+    written: usize,
+    max_bytes: usize,
+    /// Drop all newlines written, unless `keep_newlines` is set for content
+    /// whose newlines matter (code, raw HTML).
+    strip_newlines: bool,
+    keep_newlines: bool,
 }
 
 impl Write for WriteWithLast<'_> {
@@ -67,10 +552,25 @@ impl Write for WriteWithLast<'_> {
 
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         let l = buf.len();
+        if self.strip_newlines && !self.keep_newlines && buf.contains(&b'\n') {
+            let stripped: Vec<u8> = buf.iter().copied().filter(|&b| b != b'\n').collect();
+            if self.written.saturating_add(stripped.len()) > self.max_bytes {
+                return Err(RenderError::LimitExceeded(RenderLimit::OutputBytes).into());
+            }
+            self.last_was_lf.set(buf[l - 1] == 10);
+            self.output.write_all(&stripped)?;
+            self.written += stripped.len();
+            return Ok(l);
+        }
+        if self.written.saturating_add(l) > self.max_bytes {
+            return Err(RenderError::LimitExceeded(RenderLimit::OutputBytes).into());
+        }
         if l > 0 {
             self.last_was_lf.set(buf[l - 1] == 10);
         }
-        self.output.write(buf)
+        let written = self.output.write(buf)?;
+        self.written += written;
+        Ok(written)
     }
 }
 
@@ -93,18 +593,40 @@ impl Write for WriteWithLast<'_> {
 /// assert_eq!("stuff-1".to_string(), anchorizer.anchorize("Stuff".to_string()));
 /// ```
 #[derive(Debug, Default)]
-pub struct Anchorizer(HashSet<String>);
+pub struct Anchorizer {
+    ids: HashSet<String>,
+    pinned: HashSet<String>,
+    fallback: bool,
+    fallbacks: usize,
+}
 
 impl Anchorizer {
     /// Construct a new anchorizer.
     pub fn new() -> Self {
-        Anchorizer(HashSet::new())
+        Default::default()
+    }
+
+    /// Construct an anchorizer which falls back to numbered ids for headers
+    /// without a usable anchor.
+    ///
+    /// The anchor is chosen in this order:
+    /// 1. the anchorized header, if the header has any letter or digit (this
+    ///    includes CJK and other non-latin scripts),
+    /// 2. otherwise `section-{n}`, where `n` counts the fallback ids handed
+    ///    out so far, starting at 1. Headers of only emoji or punctuation get
+    ///    stable ids this way, as long as their order does not change.
+    pub fn with_fallback() -> Self {
+        Anchorizer {
+            fallback: true,
+            ..Default::default()
+        }
     }
 
     /// Returns a String that has been converted into an anchor using the
     /// GFM algorithm, which involves changing spaces to dashes, removing
     /// problem characters and, if needed, adding a suffix to make the
-    /// resultant anchor unique.
+    /// resultant anchor unique. A `rewriter` is applied to the anchor before
+    /// it is made unique.
     ///
     /// ```
     /// use comrak::Anchorizer;
@@ -115,24 +637,66 @@ impl Anchorizer {
     ///
     /// assert_eq!("ticks-arent-in".to_string(), anchorizer.anchorize(source.to_string()));
     /// ```
-    pub fn anchorize(&mut self, header: impl AsRef<str>) -> String {
-        let id = anchor::anchorize(header.as_ref());
+    pub fn anchorize(
+        &mut self,
+        header: impl AsRef<str>,
+        rewriter: Option<&dyn Fn(&str) -> String>,
+    ) -> String {
+        let header = header.as_ref();
+        let id = if self.fallback && !header.chars().any(char::is_alphanumeric) {
+            self.fallbacks += 1;
+            Cow::Owned(format!("section-{}", self.fallbacks))
+        } else {
+            anchor::anchorize(header)
+        };
+        let id = match rewriter {
+            Some(rewriter) => Cow::Owned(rewriter(&id)),
+            None => id,
+        };
+        self.make_unique(&id)
+    }
+
+    /// Marks `id` as taken, so generated anchors do not collide with it.
+    pub fn reserve(&mut self, id: impl Into<String>) {
+        self.ids.insert(id.into());
+    }
+
+    /// Marks `id` as pinned by a header, so generated anchors do not take it
+    /// before the header does with [`Anchorizer::pin`].
+    pub fn reserve_pinned(&mut self, id: impl Into<String>) {
+        self.pinned.insert(id.into());
+    }
+
+    /// Returns the pinned `id` of a header as it is, unless it is taken
+    /// already, then it is made unique like generated anchors.
+    pub fn pin(&mut self, id: &str) -> String {
+        self.pinned.remove(id);
+        self.make_unique(id)
+    }
+
+    /// Returns the next `section-{n}` anchor, sharing the numbering with the
+    /// fallback ids of [`Anchorizer::with_fallback`].
+    pub fn section_anchor(&mut self) -> String {
+        self.fallbacks += 1;
+        self.make_unique(&format!("section-{}", self.fallbacks))
+    }
 
+    fn make_unique(&mut self, id: &str) -> String {
         let mut uniq = 0;
         let id = loop {
             let anchor = if uniq == 0 {
-                Cow::from(id.as_ref())
+                Cow::from(id)
             } else {
                 Cow::from(format!("{}_{}", id, uniq + 1))
             };
 
-            if !self.0.contains(&*anchor) {
+            if !self.ids.contains(&*anchor) && !self.pinned.contains(&*anchor) {
                 break anchor;
             }
 
             uniq += 1;
         };
-        self.0.insert(id.to_string());
+        self.ids.insert(id.to_string());
         id.to_string()
     }
 }
@@ -140,13 +704,32 @@ impl Anchorizer {
 struct HtmlFormatter<'o, 'c> {
     output: &'o mut WriteWithLast<'o>,
     options: &'o Options<'c>,
+    m2h_options: &'o M2HOptions<'o>,
     anchorizer: Anchorizer,
     footnote_ix: u32,
     written_footnote_ix: u32,
     plugins: &'o ComrakPlugins<'o>,
+    diagnostics: Vec<RenderDiagnostic>,
+    /// The nesting depth of the node being formatted.
+    depth: usize,
+    list_depth: usize,
+    heading_numbers: [usize; 6],
+    heading_number_base: Option<usize>,
+    smart_prev: Option<char>,
+    footnote_contents: HashMap<String, String>,
+    table_captions: BTreeSet<Sourcepos>,
+    summary_marked: bool,
+    rendered_nodes: usize,
+    max_nodes: usize,
+    prev_heading_level: Option<u8>,
+    anchors: HashMap<String, Sourcepos>,
+    equation_number: usize,
+    external_origins: Vec<String>,
+    /// Levels of the headings whose `<section>` is open, see `wrap_sections`.
+    open_sections: Vec<u8>,
 }
 
-fn tagfilter(literal: &[u8]) -> bool {
+fn tagfilter(literal: &[u8], extra: &[String]) -> bool {
     static TAGFILTER_BLACKLIST: [&str; 9] = [
         "title",
         "textarea",
@@ -169,19 +752,29 @@ fn tagfilter(literal: &[u8]) -> bool {
     }
 
     let lc = unsafe { String::from_utf8_unchecked(literal[i..].to_vec()) }.to_lowercase();
-    for t in TAGFILTER_BLACKLIST.iter() {
-        if lc.starts_with(t) {
+    let extra = extra.iter().map(|t| t.to_lowercase());
+    for t in TAGFILTER_BLACKLIST
+        .iter()
+        .map(|t| t.to_string())
+        .chain(extra)
+    {
+        if lc.starts_with(&t) {
             let j = i + t.len();
-            return isspace(literal[j])
-                || literal[j] == b'>'
-                || (literal[j] == b'/' && literal.len() >= j + 2 && literal[j + 1] == b'>');
+            return match literal.get(j) {
+                Some(&c) => {
+                    isspace(c)
+                        || c == b'>'
+                        || (c == b'/' && literal.len() >= j + 2 && literal[j + 1] == b'>')
+                }
+                None => false,
+            };
         }
     }
 
     false
 }
 
-fn tagfilter_block(input: &[u8], o: &mut dyn Write) -> io::Result<()> {
+fn tagfilter_block(input: &[u8], extra: &[String], o: &mut dyn Write) -> io::Result<()> {
     let size = input.len();
     let mut i = 0;
 
@@ -199,7 +792,7 @@ fn tagfilter_block(input: &[u8], o: &mut dyn Write) -> io::Result<()> {
             break;
         }
 
-        if tagfilter(&input[i..]) {
+        if tagfilter(&input[i..], extra) {
             o.write_all(b"&lt;")?;
         } else {
             o.write_all(b"<")?;
@@ -215,6 +808,46 @@ fn dangerous_url(_: &[u8]) -> bool {
     false
 }
 
+/// Returns the summary of a ```` ```details Title ```` block, `None` for other
+/// code blocks.
+fn details_title(info: &str) -> Option<&str> {
+    match info.split_once(char::is_whitespace) {
+        Some(("details", title)) => Some(title.trim()),
+        None if info == "details" => Some(""),
+        _ => None,
+    }
+}
+
+/// The id of a ```` ```live-example <id> ```` block. Blocks without a valid id
+/// (ASCII letters, digits, `-` and `_`) are rendered as code.
+fn live_example_id(info: &str) -> Option<&str> {
+    match info.split_once(char::is_whitespace) {
+        Some(("live-example", id)) => {
+            let id = id.trim();
+            (!id.is_empty()
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'))
+            .then_some(id)
+        }
+        _ => None,
+    }
+}
+
+/// Display text for an autolinked URL: without the scheme and cut to
+/// `max_len` characters followed by an ellipsis.
+fn shorten_url(url: &str, max_len: usize) -> Cow<'_, str> {
+    let text = url
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .or_else(|| url.strip_prefix("mailto:"))
+        .unwrap_or(url);
+    match text.char_indices().nth(max_len) {
+        Some((end, _)) => Cow::Owned(format!("{}…", &text[..end])),
+        None => Cow::Borrowed(text),
+    }
+}
+
 /// Writes buffer to output, escaping anything that could be interpreted as an
 /// HTML tag.
 ///
@@ -337,16 +970,38 @@ where
 {
     fn new(
         options: &'o ComrakOptions<'c>,
+        m2h_options: &'o M2HOptions<'o>,
         output: &'o mut WriteWithLast<'o>,
         plugins: &'o Plugins,
     ) -> Self {
         HtmlFormatter {
             options,
+            m2h_options,
             output,
-            anchorizer: Anchorizer::new(),
+            anchorizer: if m2h_options.anchor_fallback {
+                Anchorizer::with_fallback()
+            } else {
+                Anchorizer::new()
+            },
             footnote_ix: 0,
             written_footnote_ix: 0,
             plugins,
+            diagnostics: vec![],
+            depth: 0,
+            list_depth: 0,
+            heading_numbers: [0; 6],
+            heading_number_base: None,
+            smart_prev: None,
+            footnote_contents: HashMap::new(),
+            table_captions: BTreeSet::new(),
+            summary_marked: false,
+            rendered_nodes: 0,
+            max_nodes: usize::MAX,
+            prev_heading_level: None,
+            anchors: HashMap::new(),
+            equation_number: 0,
+            external_origins: vec![],
+            open_sections: vec![],
         }
     }
 
@@ -365,7 +1020,63 @@ where
         escape_href(&mut self.output, buffer)
     }
 
+    /// Opens the `<section>` of a heading of `level`, closing the sections of
+    /// preceding headings of the same or a higher level.
+    fn open_section(&mut self, level: u8, id: Option<&str>) -> io::Result<()> {
+        self.close_sections(level)?;
+        self.open_sections.push(level);
+        self.output.write_all(b"<section")?;
+        if let Some(id) = id {
+            self.output.write_all(b" aria-labelledby=\"")?;
+            self.escape(id.as_bytes())?;
+            self.output.write_all(b"\"")?;
+        }
+        self.output.write_all(b">\n")
+    }
+
+    /// Closes the open sections of headings of `level` or deeper, all of
+    /// them for level `0`.
+    fn close_sections(&mut self, level: u8) -> io::Result<()> {
+        while self.open_sections.last().is_some_and(|open| *open >= level) {
+            self.open_sections.pop();
+            self.cr()?;
+            self.output.write_all(b"</section>\n")?;
+        }
+        Ok(())
+    }
+
+    /// Escapes inline code, with `<wbr>` after the `/`, `.`, `_` and `-` of
+    /// tokens too long to fit narrow screens.
+    fn escape_with_wbr(&mut self, literal: &str) -> io::Result<()> {
+        for token in literal.split_inclusive(char::is_whitespace) {
+            if token.chars().count() < WBR_MIN_TOKEN_CHARS {
+                self.escape(token.as_bytes())?;
+                continue;
+            }
+            let mut parts = token.split_inclusive(['/', '.', '_', '-']).peekable();
+            while let Some(part) = parts.next() {
+                self.escape(part.as_bytes())?;
+                if parts.peek().is_some() {
+                    self.output.write_all(b"<wbr>")?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn format<'a>(&mut self, node: &'a AstNode<'a>, plain: bool, locale: Locale) -> io::Result<()> {
+        self.format_at_depth(node, plain, locale, 0)
+    }
+
+    /// Formats `node` as if it was nested `depth` levels deep, for content
+    /// rendered on behalf of another node.
+    fn format_at_depth<'a>(
+        &mut self,
+        node: &'a AstNode<'a>,
+        plain: bool,
+        locale: Locale,
+        depth: usize,
+    ) -> io::Result<()> {
         // Traverse the AST iteratively using a work stack, with pre- and
         // post-child-traversal phases. During pre-order traversal render the
         // opening tags, then push the node back onto the stack for the
@@ -376,11 +1087,33 @@ where
             Pre,
             Post,
         }
-        let mut stack = vec![(node, plain, Phase::Pre, Flag::None)];
+        let mut stack = vec![(node, plain, Phase::Pre, Flag::None, depth)];
+        let mut truncated = false;
 
-        while let Some((node, plain, phase, flag)) = stack.pop() {
+        while let Some((node, plain, phase, flag, depth)) = stack.pop() {
             match phase {
                 Phase::Pre => {
+                    // Drop everything nested too deeply, the closing tags of the
+                    // ancestors are still written so the output stays balanced.
+                    if depth > self.m2h_options.max_depth {
+                        if !truncated {
+                            truncated = true;
+                            self.diagnostics.push(RenderDiagnostic {
+                                kind: RenderDiagnosticKind::MaxDepthExceeded,
+                                sourcepos: node.data.borrow().sourcepos,
+                                message: format!(
+                                    "nesting deeper than {} levels was not rendered",
+                                    self.m2h_options.max_depth
+                                ),
+                            });
+                        }
+                        continue;
+                    }
+                    self.rendered_nodes += 1;
+                    if self.rendered_nodes > self.max_nodes {
+                        return Err(RenderError::LimitExceeded(RenderLimit::Nodes).into());
+                    }
+                    let mut skip_children = false;
                     let new_plain = if plain {
                         match node.data.borrow().value {
                             NodeValue::Text(ref literal)
@@ -398,14 +1131,18 @@ where
                         }
                         plain
                     } else {
+                        self.depth = depth;
                         let (new_plain, new_flag) = self.format_node(node, true, flag, locale)?;
+                        skip_children = matches!(new_flag, Flag::SkipChildren);
 
-                        stack.push((node, false, Phase::Post, new_flag));
+                        stack.push((node, false, Phase::Post, new_flag, depth));
                         new_plain
                     };
 
-                    for ch in node.reverse_children() {
-                        stack.push((ch, new_plain, Phase::Pre, Flag::None));
+                    if !skip_children {
+                        for ch in node.reverse_children() {
+                            stack.push((ch, new_plain, Phase::Pre, Flag::None, depth + 1));
+                        }
                     }
                 }
                 Phase::Post => {
@@ -419,18 +1156,18 @@ where
     }
 
     fn collect_text<'a>(node: &'a AstNode<'a>, output: &mut Vec<u8>) {
-        match node.data.borrow().value {
-            NodeValue::Text(ref literal) | NodeValue::Code(NodeCode { ref literal, .. }) => {
-                output.extend_from_slice(literal.as_bytes())
-            }
-            NodeValue::LineBreak | NodeValue::SoftBreak => output.push(b' '),
-            NodeValue::Math(NodeMath { ref literal, .. }) => {
-                output.extend_from_slice(literal.as_bytes())
-            }
-            _ => {
-                for n in node.children() {
-                    Self::collect_text(n, output);
+        // Iterative, so deeply nested input cannot overflow the stack.
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            match node.data.borrow().value {
+                NodeValue::Text(ref literal) | NodeValue::Code(NodeCode { ref literal, .. }) => {
+                    output.extend_from_slice(literal.as_bytes())
+                }
+                NodeValue::LineBreak | NodeValue::SoftBreak => output.push(b' '),
+                NodeValue::Math(NodeMath { ref literal, .. }) => {
+                    output.extend_from_slice(literal.as_bytes())
                 }
+                _ => stack.extend(node.reverse_children()),
             }
         }
     }
@@ -442,42 +1179,95 @@ where
         flag: Flag,
         locale: Locale,
     ) -> io::Result<(bool, Flag)> {
+        if self.m2h_options.smart_punctuation && entering && node.data.borrow().value.block() {
+            self.smart_prev = None;
+        }
+        if entering {
+            if let NodeValue::Heading(ref nch) = node.data.borrow().value {
+                self.check_heading_level(node, nch.level);
+            }
+        }
         match node.data.borrow().value {
-            NodeValue::Document => (),
-            NodeValue::FrontMatter(_) => (),
+            NodeValue::Document => {
+                if !entering {
+                    self.close_sections(0)?;
+                }
+            }
+            NodeValue::FrontMatter(ref front_matter) => {
+                if entering && self.m2h_options.emit_frontmatter_json {
+                    self.render_frontmatter_json(node, front_matter)?;
+                }
+            }
             NodeValue::BlockQuote => {
+                // Captions are written by the table following them.
+                if entering
+                    && node.next_sibling().is_some_and(|next| {
+                        matches!(next.data.borrow().value, NodeValue::Table(..))
+                            && self.table_captions.contains(&next.data.borrow().sourcepos)
+                    })
+                {
+                    return Ok((false, Flag::SkipChildren));
+                } else if let Flag::SkipChildren = flag {
+                    return Ok((false, Flag::None));
+                }
                 self.cr()?;
                 if entering {
-                    let note_card = is_callout(node, locale);
+                    let note_card = if self.m2h_options.note_cards {
+                        is_callout(node, locale, self.m2h_options.keep_card_markers)
+                    } else {
+                        None
+                    };
                     match note_card {
-                        Some(NoteCard::Callout) => {
-                            self.output.write_all(b"<div class=\"callout\"")?;
-                            self.render_sourcepos(node)?;
-                            self.output.write_all(b">\n")?;
-                            return Ok((false, Flag::Card));
-                        }
-                        Some(NoteCard::Note) => {
-                            self.output
-                                .write_all(b"<div class=\"notecard note\" data-add-note")?;
-                            self.render_sourcepos(node)?;
-                            self.output.write_all(b">\n")?;
-                            return Ok((false, Flag::Card));
-                        }
-                        Some(NoteCard::Warning) => {
-                            self.output
-                                .write_all(b"<div class=\"notecard warning\" data-add-warning")?;
+                        Some((card, attributes)) => {
+                            let (class, data_attribute) = match card {
+                                NoteCard::Callout => ("callout", ""),
+                                NoteCard::Note => ("notecard note", " data-add-note"),
+                                NoteCard::Warning => ("notecard warning", " data-add-warning"),
+                                NoteCard::Caption => unreachable!("captions are no callouts"),
+                            };
+                            self.output.write_all(b"<div class=\"")?;
+                            self.output.write_all(class.as_bytes())?;
+                            for class in &attributes.classes {
+                                self.output.write_all(b" ")?;
+                                self.escape(class.as_bytes())?;
+                            }
+                            self.output.write_all(b"\"")?;
+                            if let Some(ref id) = attributes.id {
+                                self.output.write_all(b" id=\"")?;
+                                self.escape(id.as_bytes())?;
+                                self.output.write_all(b"\"")?;
+                            }
+                            self.output.write_all(data_attribute.as_bytes())?;
                             self.render_sourcepos(node)?;
                             self.output.write_all(b">\n")?;
                             return Ok((false, Flag::Card));
                         }
                         None => {
                             self.output.write_all(b"<blockquote")?;
+                            let citation = if self.m2h_options.blockquote_citations {
+                                take_citation(node)
+                            } else {
+                                None
+                            };
+                            if let Some(ref url) = citation {
+                                self.output.write_all(b" cite=\"")?;
+                                self.escape_href(url.as_bytes())?;
+                                self.output.write_all(b"\"")?;
+                            }
                             self.render_sourcepos(node)?;
                             self.output.write_all(b">\n")?;
+                            if let Some(url) = citation {
+                                return Ok((false, Flag::Citation(url)));
+                            }
                         }
                     };
                 } else if let Flag::Card = flag {
                     self.output.write_all(b"</div>\n")?;
+                } else if let Flag::Citation(ref url) = flag {
+                    self.output.write_all(b"<footer><cite>")?;
+                    self.escape(url.as_bytes())?;
+                    self.output
+                        .write_all(b"</cite></footer>\n</blockquote>\n")?;
                 } else {
                     self.output.write_all(b"</blockquote>\n")?;
                 }
@@ -491,6 +1281,10 @@ where
                             if nl.is_task_list && self.options.render.tasklist_classes {
                                 self.output.write_all(b" class=\"contains-task-list\"")?;
                             }
+                            self.render_list_depth(self.list_depth)?;
+                            if nl.is_task_list {
+                                self.render_task_progress(node)?;
+                            }
                             self.render_sourcepos(node)?;
                             self.output.write_all(b">\n")?;
                         }
@@ -499,7 +1293,16 @@ where
                             if nl.is_task_list && self.options.render.tasklist_classes {
                                 self.output.write_all(b" class=\"contains-task-list\"")?;
                             }
+                            self.render_list_depth(self.list_depth)?;
+                            if nl.is_task_list {
+                                self.render_task_progress(node)?;
+                            }
                             self.render_sourcepos(node)?;
+                            if self.m2h_options.list_type_hints {
+                                if let Some(list_type) = take_list_type(node) {
+                                    write!(self.output, " type=\"{list_type}\"")?;
+                                }
+                            }
                             if nl.start == 1 {
                                 self.output.write_all(b">\n")?;
                             } else {
@@ -507,16 +1310,21 @@ where
                             }
                         }
                     }
-                } else if nl.list_type == ListType::Bullet {
-                    self.output.write_all(b"</ul>\n")?;
+                    self.list_depth += 1;
                 } else {
-                    self.output.write_all(b"</ol>\n")?;
+                    self.list_depth -= 1;
+                    if nl.list_type == ListType::Bullet {
+                        self.output.write_all(b"</ul>\n")?;
+                    } else {
+                        self.output.write_all(b"</ol>\n")?;
+                    }
                 }
             }
             NodeValue::Item(..) => {
                 if entering {
                     self.cr()?;
                     self.output.write_all(b"<li")?;
+                    self.render_list_depth(self.list_depth.saturating_sub(1))?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                 } else {
@@ -533,15 +1341,28 @@ where
                     self.output.write_all(b"</dl>\n")?;
                 }
             }
-            NodeValue::DescriptionItem(..) => (),
+            NodeValue::DescriptionItem(..) => {
+                if self.m2h_options.group_description_items {
+                    if entering {
+                        self.cr()?;
+                        self.output.write_all(b"<div class=\"dl-group\">\n")?;
+                    } else {
+                        self.output.write_all(b"</div>\n")?;
+                    }
+                }
+            }
             NodeValue::DescriptionTerm => {
                 if entering {
                     self.cr()?;
+                    if self.m2h_options.group_description_items && is_in_flat_dl(node) {
+                        self.output.write_all(b"<div class=\"dl-group\">\n")?;
+                    }
                     self.output.write_all(b"<dt")?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                 } else {
                     self.output.write_all(b"</dt>\n")?;
+                    self.close_flat_dl_group(node)?;
                 }
             }
             NodeValue::DescriptionDetails => {
@@ -552,33 +1373,97 @@ where
                     self.output.write_all(b">")?;
                 } else {
                     self.output.write_all(b"</dd>\n")?;
+                    self.close_flat_dl_group(node)?;
                 }
             }
             NodeValue::Heading(ref nch) => match self.plugins.render.heading_adapter {
                 None => {
                     if entering {
                         self.cr()?;
-                        write!(self.output, "<h{}", nch.level)?;
-                        if self.options.extension.header_ids.is_some() {
+                        let custom_id = self
+                            .m2h_options
+                            .custom_heading_ids
+                            .then(|| custom_heading_id(node, true))
+                            .flatten();
+                        let mut update_id = false;
+                        let mut id = None;
+                        if self.options.extension.header_ids.is_some()
+                            && !self.m2h_options.suppress_heading_ids
+                        {
                             let mut text_content = Vec::with_capacity(20);
                             Self::collect_text(node, &mut text_content);
 
                             let raw_id = String::from_utf8(text_content).unwrap();
                             let is_templ = raw_id.contains(DELIM_START);
-                            if is_templ {
-                                write!(self.output, " data-update-id")?;
+                            if custom_id.is_none() && is_templ {
+                                self.diagnostics.push(RenderDiagnostic {
+                                    kind: RenderDiagnosticKind::TemplatedHeading,
+                                    sourcepos: node.data.borrow().sourcepos,
+                                    message: "heading was not expanded before rendering"
+                                        .to_string(),
+                                });
+                                update_id = true;
                             } else {
-                                let id = self.anchorizer.anchorize(&raw_id);
-                                write!(self.output, " id=\"{}\"", id)?;
+                                id = match self.m2h_options.empty_heading_ids {
+                                    _ if custom_id.is_some() => {
+                                        custom_id.map(|id| self.anchorizer.pin(&id))
+                                    }
+                                    EmptyHeadingIds::Omit if raw_id.trim().is_empty() => None,
+                                    EmptyHeadingIds::Section if raw_id.trim().is_empty() => {
+                                        Some(self.anchorizer.section_anchor())
+                                    }
+                                    _ => Some(
+                                        self.anchorizer.anchorize(
+                                            &raw_id,
+                                            self.m2h_options
+                                                .anchor_id_rewriter
+                                                .as_deref()
+                                                .map(|f| f as &dyn Fn(&str) -> String),
+                                        ),
+                                    ),
+                                }
+                                .map(|id| {
+                                    match &self.m2h_options.heading_id_prefix {
+                                        Some(prefix) => format!("{prefix}{id}"),
+                                        None => id,
+                                    }
+                                });
                             };
                         }
+                        if self.m2h_options.wrap_sections
+                            && node.parent().is_some_and(|parent| {
+                                matches!(parent.data.borrow().value, NodeValue::Document)
+                            })
+                        {
+                            self.open_section(nch.level, id.as_deref())?;
+                        }
+                        write!(self.output, "<h{}", nch.level)?;
+                        if update_id {
+                            write!(self.output, " data-update-id")?;
+                        }
+                        if let Some(id) = id {
+                            self.output.write_all(b" id=\"")?;
+                            self.escape(id.as_bytes())?;
+                            self.output.write_all(b"\"")?;
+                            self.anchors.insert(id, node.data.borrow().sourcepos);
+                        }
                         self.render_sourcepos(node)?;
                         self.output.write_all(b">")?;
+                        if self.m2h_options.auto_number_headings {
+                            let number = self.next_heading_number(nch.level);
+                            write!(
+                                self.output,
+                                "<span class=\"heading-number\">{number}</span> "
+                            )?;
+                        }
                     } else {
                         writeln!(self.output, "</h{}>", nch.level)?;
                     }
                 }
                 Some(adapter) => {
+                    if entering && self.m2h_options.custom_heading_ids {
+                        custom_heading_id(node, true);
+                    }
                     let mut text_content = Vec::with_capacity(20);
                     Self::collect_text(node, &mut text_content);
                     let content = String::from_utf8(text_content).unwrap();
@@ -607,12 +1492,26 @@ where
                 if entering {
                     if ncb.info.eq("math") {
                         self.render_math_code_block(node, &ncb.literal)?;
+                    } else if let Some(title) = details_title(&ncb.info) {
+                        self.render_details(node, title, &ncb.literal, locale)?;
+                    } else if let Some(id) = live_example_id(&ncb.info) {
+                        self.render_live_example(node, id)?;
                     } else {
                         self.cr()?;
 
+                        let collapsed = self
+                            .m2h_options
+                            .collapse_code_over
+                            .is_some_and(|max_lines| ncb.literal.lines().count() > max_lines);
+                        if collapsed {
+                            self.output.write_all(
+                                b"<details class=\"code\"><summary>Show code</summary>\n",
+                            )?;
+                        }
+
                         let mut first_tag = 0;
-                        let mut pre_attributes: HashMap<String, String> = HashMap::new();
-                        let mut code_attributes: HashMap<String, String> = HashMap::new();
+                        let mut pre_attributes: Vec<(String, String)> = Vec::new();
+                        let mut code_attributes: Vec<(String, String)> = Vec::new();
                         let code_attr: String;
 
                         let literal = &ncb.literal.as_bytes();
@@ -626,22 +1525,33 @@ where
                             let lang_str = str::from_utf8(&info[..first_tag]).unwrap();
                             let info_str = str::from_utf8(&info[first_tag..]).unwrap().trim();
 
+                            if let Some(known_languages) = &self.m2h_options.known_languages {
+                                let lang = lang_str.strip_suffix("-nolint").unwrap_or(lang_str);
+                                if !known_languages.contains(lang) {
+                                    self.diagnostics.push(RenderDiagnostic {
+                                        kind: RenderDiagnosticKind::UnknownLanguage,
+                                        sourcepos: node.data.borrow().sourcepos,
+                                        message: format!("unknown code block language: {lang}"),
+                                    });
+                                }
+                            }
+
                             if self.options.render.github_pre_lang {
-                                pre_attributes.insert(String::from("lang"), lang_str.to_string());
+                                pre_attributes.push((String::from("lang"), lang_str.to_string()));
 
                                 if self.options.render.full_info_string && !info_str.is_empty() {
-                                    pre_attributes.insert(
+                                    pre_attributes.push((
                                         String::from("data-meta"),
                                         info_str.trim().to_string(),
-                                    );
+                                    ));
                                 }
                             } else {
                                 code_attr = format!("language-{}", lang_str);
-                                code_attributes.insert(String::from("class"), code_attr);
+                                code_attributes.push((String::from("class"), code_attr));
 
                                 if self.options.render.full_info_string && !info_str.is_empty() {
                                     code_attributes
-                                        .insert(String::from("data-meta"), info_str.to_string());
+                                        .push((String::from("data-meta"), info_str.to_string()));
                                 }
                             }
                         }
@@ -649,54 +1559,80 @@ where
                         if self.options.render.sourcepos {
                             let ast = node.data.borrow();
                             pre_attributes
-                                .insert("data-sourcepos".to_string(), ast.sourcepos.to_string());
+                                .push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
                         }
 
                         match self.plugins.render.codefence_syntax_highlighter {
                             None => {
                                 pre_attributes.extend(code_attributes);
-                                let _with_code = if let Some(cls) = pre_attributes.get_mut("class")
+                                // The class always comes first, the rest keep their order.
+                                let class = match pre_attributes
+                                    .iter()
+                                    .position(|(attr, _)| attr == "class")
                                 {
-                                    if !ncb.info.is_empty() {
+                                    Some(i) if !ncb.info.is_empty() => {
+                                        pre_attributes.remove(i);
                                         let langs = ncb
                                             .info
                                             .split_ascii_whitespace()
                                             .map(|s| s.strip_suffix("-nolint").unwrap_or(s))
                                             .join(" ");
-
-                                        *cls = format!("brush: {langs} notranslate",);
-                                        &ncb.info != "plain"
-                                    } else {
-                                        *cls = "notranslate".to_string();
-                                        false
+                                        format!("brush: {langs} notranslate")
                                     }
-                                } else {
-                                    pre_attributes.insert("class".into(), "notranslate".into());
-                                    false
+                                    Some(i) => {
+                                        pre_attributes.remove(i);
+                                        "notranslate".to_string()
+                                    }
+                                    None => "notranslate".to_string(),
                                 };
+                                pre_attributes.insert(0, ("class".into(), class));
+                                // Let linters know which languages opted out of linting.
+                                let nolint_langs = ncb
+                                    .info
+                                    .split_ascii_whitespace()
+                                    .filter_map(|s| s.strip_suffix("-nolint"))
+                                    .join(" ");
+                                if !nolint_langs.is_empty() {
+                                    pre_attributes.push(("data-nolint".into(), "true".into()));
+                                    pre_attributes.push(("data-nolint-langs".into(), nolint_langs));
+                                }
                                 write_opening_tag(self.output, "pre", pre_attributes)?;
+                                self.output.keep_newlines = true;
                                 self.escape(literal)?;
+                                self.output.keep_newlines = false;
                                 self.output.write_all(b"</pre>\n")?
                             }
                             Some(highlighter) => {
-                                highlighter.write_pre_tag(self.output, pre_attributes)?;
-                                highlighter.write_code_tag(self.output, code_attributes)?;
+                                highlighter.write_pre_tag(
+                                    self.output,
+                                    pre_attributes.into_iter().collect(),
+                                )?;
+                                highlighter.write_code_tag(
+                                    self.output,
+                                    code_attributes.into_iter().collect(),
+                                )?;
 
+                                self.output.keep_newlines = true;
                                 highlighter.write_highlighted(
                                     self.output,
                                     str::from_utf8(&info[..first_tag]).ok(),
                                     &ncb.literal,
                                 )?;
+                                self.output.keep_newlines = false;
 
                                 self.output.write_all(b"</code></pre>\n")?
                             }
                         }
+                        if collapsed {
+                            self.output.write_all(b"</details>\n")?;
+                        }
                     }
                 }
             }
             NodeValue::HtmlBlock(ref nhb) => {
                 // No sourcepos.
                 if entering {
+                    self.note_external_html(&nhb.literal);
                     let is_marco = nhb.literal.starts_with("<!-- ks____");
                     if !is_marco {
                         self.cr()?;
@@ -709,15 +1645,24 @@ where
                     } else {
                         nhb.literal.as_bytes()
                     };
+                    self.output.keep_newlines = true;
                     if self.options.render.escape {
                         self.escape(literal)?;
                     } else if !self.options.render.unsafe_ {
-                        self.output.write_all(b"<!-- raw HTML omitted -->")?;
+                        self.raw_html_omitted(node, literal)?;
+                    } else if let Some(config) = &self.m2h_options.sanitize {
+                        let html = sanitize_html(&String::from_utf8_lossy(literal), config);
+                        self.output.write_all(html.as_bytes())?;
                     } else if self.options.extension.tagfilter {
-                        tagfilter_block(literal, &mut self.output)?;
+                        tagfilter_block(
+                            literal,
+                            &self.m2h_options.tagfilter_extra,
+                            &mut self.output,
+                        )?;
                     } else {
                         self.output.write_all(literal)?;
                     }
+                    self.output.keep_newlines = false;
                     if !is_marco {
                         self.cr()?;
                     }
@@ -728,7 +1673,8 @@ where
                     self.cr()?;
                     self.output.write_all(b"<hr")?;
                     self.render_sourcepos(node)?;
-                    self.output.write_all(b" />\n")?;
+                    self.close_void_tag()?;
+                    self.output.write_all(b"\n")?;
                 }
             }
             NodeValue::Paragraph => {
@@ -752,6 +1698,20 @@ where
                     if entering {
                         self.cr()?;
                         self.output.write_all(b"<p")?;
+                        if self.m2h_options.mark_summary_paragraph
+                            && !self.summary_marked
+                            && node.parent().is_some_and(|parent| {
+                                matches!(parent.data.borrow().value, NodeValue::Document)
+                            })
+                        {
+                            self.summary_marked = true;
+                            if let Some(ref class) = self.m2h_options.summary_class {
+                                self.output.write_all(b" class=\"")?;
+                                escape(self.output, class.as_bytes())?;
+                                self.output.write_all(b"\"")?;
+                            }
+                            self.output.write_all(b" data-summary")?;
+                        }
                         self.render_sourcepos(node)?;
                         self.output.write_all(b">")?;
                     } else {
@@ -768,30 +1728,56 @@ where
                 }
             }
             NodeValue::Text(ref literal) => {
-                // Nowhere to put sourcepos.
+                // Nowhere to put sourcepos, unless we add an element for it.
                 if entering {
-                    self.escape(literal.as_bytes())?;
+                    let wrap = self.m2h_options.text_sourcepos
+                        && self.options.render.experimental_inline_sourcepos
+                        && self.options.render.sourcepos
+                        && node.data.borrow().sourcepos.start.line > 0;
+                    if wrap {
+                        self.output.write_all(b"<span")?;
+                        self.render_sourcepos(node)?;
+                        self.output.write_all(b">")?;
+                    }
+                    self.write_text(literal, locale)?;
+                    if wrap {
+                        self.output.write_all(b"</span>")?;
+                    }
                 }
             }
             NodeValue::LineBreak => {
                 // Unreliable sourcepos.
                 if entering {
+                    self.smart_prev = Some('\n');
                     self.output.write_all(b"<br")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
-                    self.output.write_all(b" />\n")?;
+                    self.close_void_tag()?;
+                    self.output.write_all(b"\n")?;
                 }
             }
             NodeValue::SoftBreak => {
                 // Unreliable sourcepos.
                 if entering {
+                    if !self.options.render.hardbreaks
+                        && self.m2h_options.collapse_soft_breaks
+                        && node.previous_sibling().is_some_and(|prev| {
+                            matches!(prev.data.borrow().value, NodeValue::SoftBreak)
+                        })
+                    {
+                        return Ok((false, Flag::None));
+                    }
+                    self.smart_prev = Some('\n');
                     if self.options.render.hardbreaks {
                         self.output.write_all(b"<br")?;
                         if self.options.render.experimental_inline_sourcepos {
                             self.render_sourcepos(node)?;
                         }
-                        self.output.write_all(b" />\n")?;
+                        self.close_void_tag()?;
+                        self.output.write_all(b"\n")?;
+                    } else if self.m2h_options.no_newlines {
+                        self.output.write_all(b" ")?;
                     } else {
                         self.output.write_all(b"\n")?;
                     }
@@ -805,24 +1791,49 @@ where
                         self.render_sourcepos(node)?;
                     }
                     self.output.write_all(b">")?;
-                    self.escape(literal.as_bytes())?;
+                    if self.m2h_options.inline_code_wbr {
+                        self.escape_with_wbr(literal)?;
+                    } else {
+                        self.escape(literal.as_bytes())?;
+                    }
                     self.output.write_all(b"</code>")?;
+                    self.smart_prev = literal.chars().next_back().or(self.smart_prev);
                 }
             }
             NodeValue::HtmlInline(ref literal) => {
                 // No sourcepos.
                 if entering {
+                    self.note_external_html(literal);
                     let literal = literal.as_bytes();
+                    self.output.keep_newlines = true;
                     if self.options.render.escape {
                         self.escape(literal)?;
                     } else if !self.options.render.unsafe_ {
-                        self.output.write_all(b"<!-- raw HTML omitted -->")?;
-                    } else if self.options.extension.tagfilter && tagfilter(literal) {
+                        self.raw_html_omitted(node, literal)?;
+                    } else if let Some(config) = &self.m2h_options.sanitize {
+                        let html = sanitize_html(&String::from_utf8_lossy(literal), config);
+                        self.output.write_all(html.as_bytes())?;
+                    } else if self.options.extension.tagfilter
+                        && tagfilter(literal, &self.m2h_options.tagfilter_extra)
+                    {
                         self.output.write_all(b"&lt;")?;
                         self.output.write_all(&literal[1..])?;
                     } else {
                         self.output.write_all(literal)?;
                     }
+                    self.output.keep_newlines = false;
+                }
+            }
+            NodeValue::Raw(ref literal) if literal == HIGHLIGHT => {
+                // Unreliable sourcepos.
+                if entering {
+                    self.output.write_all(b"<mark")?;
+                    if self.options.render.experimental_inline_sourcepos {
+                        self.render_sourcepos(node)?;
+                    }
+                    self.output.write_all(b">")?;
+                } else {
+                    self.output.write_all(b"</mark>")?;
                 }
             }
             NodeValue::Raw(ref literal) => {
@@ -897,6 +1908,19 @@ where
                         ))
                 {
                     if entering {
+                        if let Some(glossary_url) = self.glossary_link(node, &nl.url) {
+                            let Some(glossary_url) = glossary_url else {
+                                return Ok((false, Flag::Unlinked));
+                            };
+                            self.output.write_all(b"<a")?;
+                            if self.options.render.experimental_inline_sourcepos {
+                                self.render_sourcepos(node)?;
+                            }
+                            self.output.write_all(b" class=\"glossary\" href=\"")?;
+                            self.escape_href(glossary_url.as_bytes())?;
+                            self.output.write_all(b"\">")?;
+                            return Ok((false, Flag::None));
+                        }
                         self.output.write_all(b"<a")?;
                         if self.options.render.experimental_inline_sourcepos {
                             self.render_sourcepos(node)?;
@@ -914,15 +1938,35 @@ where
                             self.output.write_all(b"\" title=\"")?;
                             self.escape(nl.title.as_bytes())?;
                         }
+                        if let Some(target) = self.redirect_target(&nl.url) {
+                            self.output.write_all(b"\" data-redirected-to=\"")?;
+                            self.escape_href(target.as_bytes())?;
+                        }
                         let mut text_content = Vec::with_capacity(20);
                         Self::collect_text(node, &mut text_content);
 
                         if text_content == url {
                             self.output.write_all(b"\" data-autolink=\"")?;
+                            if let Some(max_len) = self.m2h_options.shorten_autolink_text {
+                                self.output.write_all(b"\">")?;
+                                self.escape(shorten_url(&nl.url, max_len).as_bytes())?;
+                                return Ok((false, Flag::SkipChildren));
+                            }
                         }
                         self.output.write_all(b"\">")?;
-                    } else {
+                        if shows_url(&nl.url, &text_content) {
+                            return Ok((false, Flag::Autolink));
+                        }
+                    } else if !matches!(flag, Flag::Unlinked) {
                         self.output.write_all(b"</a>")?;
+                        if self.m2h_options.print_link_urls
+                            && matches!(flag, Flag::None)
+                            && external_origin(&nl.url).is_some()
+                        {
+                            self.output.write_all(b"<span class=\"print-url\">(")?;
+                            self.escape(nl.url.as_bytes())?;
+                            self.output.write_all(b")</span>")?;
+                        }
                     }
                 }
             }
@@ -930,6 +1974,11 @@ where
             NodeValue::Image(ref nl) => {
                 // Unreliable sourcepos.
                 if entering {
+                    self.note_external_url(&nl.url);
+                    if let Some(svg) = self.load_inline_svg(&nl.url) {
+                        self.write_inline_svg(node, &svg)?;
+                        return Ok((false, Flag::SkipChildren));
+                    }
                     self.output.write_all(b"<img")?;
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
@@ -943,22 +1992,61 @@ where
                             self.escape_href(url)?;
                         }
                     }
-                    self.output.write_all(b"\" alt=\"")?;
+                    self.output.write_all(b"\"")?;
+                    if let Some(image) = self
+                        .m2h_options
+                        .image_srcset
+                        .as_ref()
+                        .and_then(|image_srcset| image_srcset(&nl.url))
+                    {
+                        self.output.write_all(b" srcset=\"")?;
+                        self.escape(image.srcset.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                        if let Some(sizes) = image.sizes {
+                            self.output.write_all(b" sizes=\"")?;
+                            self.escape(sizes.as_bytes())?;
+                            self.output.write_all(b"\"")?;
+                        }
+                    }
+                    self.output.write_all(b" alt=\"")?;
                     return Ok((true, Flag::None));
+                } else if let Flag::SkipChildren = flag {
+                    // The image was inlined as `<svg>`.
                 } else {
                     if !nl.title.is_empty() {
                         self.output.write_all(b"\" title=\"")?;
                         self.escape(nl.title.as_bytes())?;
                     }
-                    self.output.write_all(b"\" />")?;
+                    self.output.write_all(b"\"")?;
+                    self.close_void_tag()?;
                 }
             }
             NodeValue::Table(..) => {
                 if entering {
                     self.cr()?;
+                    if self.m2h_options.table_wrapper {
+                        self.output
+                            .write_all(b"<div class=\"table-container\">\n")?;
+                    }
                     self.output.write_all(b"<table")?;
                     self.render_sourcepos(node)?;
+                    if self.m2h_options.table_sortable {
+                        self.output.write_all(b" data-sortable=\"true\"")?;
+                    }
                     self.output.write_all(b">\n")?;
+                    if let Some(caption) = node
+                        .previous_sibling()
+                        .filter(|_| self.table_captions.contains(&node.data.borrow().sourcepos))
+                        .and_then(|prev| prev.first_child())
+                    {
+                        self.output.write_all(b"<caption>")?;
+                        let depth = self.depth;
+                        for child in caption.children() {
+                            self.format_at_depth(child, false, locale, depth + 1)?;
+                        }
+                        self.depth = depth;
+                        self.output.write_all(b"</caption>\n")?;
+                    }
                 } else {
                     if !node
                         .last_child()
@@ -970,6 +2058,9 @@ where
                     }
                     self.cr()?;
                     self.output.write_all(b"</table>\n")?;
+                    if self.m2h_options.table_wrapper {
+                        self.output.write_all(b"</div>\n")?;
+                    }
                 }
             }
             NodeValue::TableRow(header) => {
@@ -1007,9 +2098,18 @@ where
                     _ => panic!(),
                 };
 
+                let mut start = node.parent().unwrap().first_child().unwrap();
+                let mut i = 0;
+                while !start.same_node(node) {
+                    i += 1;
+                    start = start.next_sibling().unwrap();
+                }
+
+                let row_header = !in_header && i == 0 && self.m2h_options.table_scopes;
+
                 if entering {
                     self.cr()?;
-                    if in_header {
+                    if in_header || row_header {
                         self.output.write_all(b"<th")?;
                         self.render_sourcepos(node)?;
                     } else {
@@ -1017,11 +2117,12 @@ where
                         self.render_sourcepos(node)?;
                     }
 
-                    let mut start = node.parent().unwrap().first_child().unwrap();
-                    let mut i = 0;
-                    while !start.same_node(node) {
-                        i += 1;
-                        start = start.next_sibling().unwrap();
+                    if self.m2h_options.table_scopes {
+                        if in_header {
+                            self.output.write_all(b" scope=\"col\"")?;
+                        } else if row_header {
+                            self.output.write_all(b" scope=\"row\"")?;
+                        }
                     }
 
                     match alignments[i] {
@@ -1037,8 +2138,27 @@ where
                         TableAlignment::None => (),
                     }
 
+                    if self.m2h_options.table_sortable
+                        && (in_header || self.m2h_options.table_sortable_body_cells)
+                    {
+                        write!(self.output, " data-column=\"{i}\"")?;
+                    }
+
                     self.output.write_all(b">")?;
-                } else if in_header {
+                    if self.m2h_options.kbd_in_tables && !in_header && !row_header {
+                        if let Some(keys) = key_combo(node) {
+                            for (n, key) in keys.iter().enumerate() {
+                                if n > 0 {
+                                    self.output.write_all(b"+")?;
+                                }
+                                self.output.write_all(b"<kbd>")?;
+                                self.escape(key.as_bytes())?;
+                                self.output.write_all(b"</kbd>")?;
+                            }
+                            return Ok((false, Flag::SkipChildren));
+                        }
+                    }
+                } else if in_header || row_header {
                     self.output.write_all(b"</th>")?;
                 } else {
                     self.output.write_all(b"</td>")?;
@@ -1047,6 +2167,7 @@ where
             NodeValue::FootnoteDefinition(ref nfd) => {
                 if entering {
                     if self.footnote_ix == 0 {
+                        self.close_sections(0)?;
                         self.output.write_all(b"<section")?;
                         self.render_sourcepos(node)?;
                         self.output
@@ -1066,7 +2187,9 @@ where
                 }
             }
             NodeValue::FootnoteReference(ref nfr) => {
-                // Unreliable sourcepos.
+                // The sourcepos covers the whole `[^name]` reference, it goes on
+                // the `<sup>` which wraps everything rendered for it. The inner
+                // `<a>` has no source of its own.
                 if entering {
                     let mut ref_id = format!("fnref-{}", nfr.name);
                     if nfr.ref_num > 1 {
@@ -1082,7 +2205,13 @@ where
                     self.escape_href(nfr.name.as_bytes())?;
                     self.output.write_all(b"\" id=\"")?;
                     self.escape_href(ref_id.as_bytes())?;
-                    write!(self.output, "\" data-footnote-ref>{}</a></sup>", nfr.ix)?;
+                    self.output.write_all(b"\" data-footnote-ref")?;
+                    if let Some(content) = self.footnote_contents.get(&nfr.name) {
+                        self.output.write_all(b" data-footnote-content=\"")?;
+                        escape(self.output, content.as_bytes())?;
+                        self.output.write_all(b"\"")?;
+                    }
+                    write!(self.output, ">{}</a></sup>", nfr.ix)?;
                 }
             }
             NodeValue::TaskItem(symbol) => {
@@ -1092,6 +2221,7 @@ where
                     if self.options.render.tasklist_classes {
                         self.output.write_all(b" class=\"task-list-item\"")?;
                     }
+                    self.render_list_depth(self.list_depth.saturating_sub(1))?;
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">")?;
                     self.output.write_all(b"<input type=\"checkbox\"")?;
@@ -1099,10 +2229,14 @@ where
                         self.output
                             .write_all(b" class=\"task-list-item-checkbox\"")?;
                     }
-                    if symbol.is_some() {
-                        self.output.write_all(b" checked=\"\"")?;
+                    match symbol {
+                        Some('x' | 'X') => self.output.write_all(b" checked=\"\"")?,
+                        Some('-') => self.output.write_all(b" data-indeterminate")?,
+                        _ => (),
                     }
-                    self.output.write_all(b" disabled=\"\" /> ")?;
+                    self.output.write_all(b" disabled=\"\"")?;
+                    self.close_void_tag()?;
+                    self.output.write_all(b" ")?;
                 } else {
                     self.output.write_all(b"</li>\n")?;
                 }
@@ -1149,10 +2283,22 @@ where
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
+                    let resolved = self
+                        .m2h_options
+                        .wikilink_resolver
+                        .as_ref()
+                        .and_then(|resolve| resolve(&nl.url));
                     self.output.write_all(b" href=\"")?;
-                    let url = nl.url.as_bytes();
-                    if self.options.render.unsafe_ || !dangerous_url(url) {
-                        self.escape_href(url)?;
+                    let url = resolved.as_ref().map_or(&nl.url, |resolved| &resolved.url);
+                    if self.options.render.unsafe_ || !dangerous_url(url.as_bytes()) {
+                        self.escape_href(url.as_bytes())?;
+                    }
+                    if let Some(title) = resolved
+                        .as_ref()
+                        .and_then(|resolved| resolved.title.as_ref())
+                    {
+                        self.output.write_all(b"\" title=\"")?;
+                        self.escape(title.as_bytes())?;
                     }
                     self.output.write_all(b"\" data-wikilink=\"true")?;
                     self.output.write_all(b"\">")?;
@@ -1191,7 +2337,15 @@ where
                     if self.options.render.experimental_inline_sourcepos {
                         self.render_sourcepos(node)?;
                     }
-                    self.output.write_all(b" class=\"spoiler\">")?;
+                    self.output.write_all(b" class=\"spoiler\"")?;
+                    match self.m2h_options.spoiler_attr {
+                        SpoilerAttr::None => {}
+                        SpoilerAttr::Focusable => self
+                            .output
+                            .write_all(b" tabindex=\"0\" aria-expanded=\"false\"")?,
+                        SpoilerAttr::Data => self.output.write_all(b" data-spoiler")?,
+                    }
+                    self.output.write_all(b">")?;
                 } else {
                     self.output.write_all(b"</span>")?;
                 }
@@ -1201,34 +2355,204 @@ where
                 self.output.write_all(net.as_bytes())?;
             }
             NodeValue::Alert(ref alert) => {
+                let (collapsible, title) = alert_collapsible(alert.title.as_deref());
                 if entering {
                     self.cr()?;
-                    self.output.write_all(b"<div class=\"markdown-alert ")?;
+                    if collapsible.is_some() {
+                        self.output.write_all(b"<details")?;
+                    } else {
+                        self.output.write_all(b"<div")?;
+                    }
+                    self.output.write_all(b" class=\"markdown-alert ")?;
                     self.output
                         .write_all(alert_type_css_class(&alert.alert_type).as_bytes())?;
                     self.output.write_all(b"\"")?;
+                    if collapsible == Some(Collapsible::Open) {
+                        self.output.write_all(b" open")?;
+                    }
                     self.render_sourcepos(node)?;
                     self.output.write_all(b">\n")?;
-                    self.output
-                        .write_all(b"<p class=\"markdown-alert-title\">")?;
-                    match alert.title {
-                        Some(ref title) => self.escape(title.as_bytes())?,
+                    if collapsible.is_some() {
+                        self.output
+                            .write_all(b"<summary class=\"markdown-alert-title\">")?;
+                    } else {
+                        self.output
+                            .write_all(b"<p class=\"markdown-alert-title\">")?;
+                    }
+                    match title {
+                        Some(title) => self.escape(title.as_bytes())?,
                         None => {
                             self.output.write_all(
-                                alert_type_default_title(&alert.alert_type).as_bytes(),
+                                alert_type_default_title(&alert.alert_type, locale).as_bytes(),
                             )?;
                         }
                     }
-                    self.output.write_all(b"</p>\n")?;
+                    if collapsible.is_some() {
+                        self.output.write_all(b"</summary>\n")?;
+                    } else {
+                        self.output.write_all(b"</p>\n")?;
+                    }
                 } else {
                     self.cr()?;
-                    self.output.write_all(b"</div>\n")?;
+                    if collapsible.is_some() {
+                        self.output.write_all(b"</details>\n")?;
+                    } else {
+                        self.output.write_all(b"</div>\n")?;
+                    }
                 }
             }
         }
         Ok((false, Flag::None))
     }
 
+    fn raw_html_omitted<'a>(&mut self, node: &'a AstNode<'a>, literal: &[u8]) -> io::Result<()> {
+        if self.m2h_options.error_on_raw_html {
+            self.diagnostics.push(RenderDiagnostic {
+                kind: RenderDiagnosticKind::RawHtml,
+                sourcepos: node.data.borrow().sourcepos,
+                message: format!(
+                    "raw HTML omitted: {}",
+                    String::from_utf8_lossy(literal).trim()
+                ),
+            });
+        }
+        self.output.write_all(b"<!-- raw HTML omitted -->")
+    }
+
+    // Renders YAML front matter as JSON in a `<script>` tag. Invalid front
+    // matter is skipped and reported as a diagnostic.
+    fn render_frontmatter_json<'a>(
+        &mut self,
+        node: &'a AstNode<'a>,
+        front_matter: &str,
+    ) -> io::Result<()> {
+        let yaml = front_matter.trim();
+        let yaml = yaml.strip_prefix("---").unwrap_or(yaml);
+        let yaml = yaml.strip_suffix("---").unwrap_or(yaml);
+        match serde_yaml_ng::from_str::<serde_json::Value>(yaml) {
+            Ok(value) => {
+                // Escape `</` so the JSON cannot close the script tag.
+                let json = serde_json::to_string(&value)
+                    .map_err(io::Error::other)?
+                    .replace("</", "<\\/");
+                self.output
+                    .write_all(b"<script type=\"application/json\" data-frontmatter>")?;
+                self.output.write_all(json.as_bytes())?;
+                self.output.write_all(b"</script>\n")?;
+            }
+            Err(e) => self.diagnostics.push(RenderDiagnostic {
+                kind: RenderDiagnosticKind::InvalidFrontMatter,
+                sourcepos: node.data.borrow().sourcepos,
+                message: format!("invalid front matter: {e}"),
+            }),
+        }
+        Ok(())
+    }
+
+    fn close_void_tag(&mut self) -> io::Result<()> {
+        if self.m2h_options.html5_void_elements {
+            self.output.write_all(b">")
+        } else {
+            self.output.write_all(b" />")
+        }
+    }
+
+    /// Counts the heading and returns its section number, e.g. `1.2`. Numbers
+    /// start at the highest heading level seen so far.
+    fn next_heading_number(&mut self, level: u8) -> String {
+        let level = usize::from(level.clamp(1, 6));
+        let base = self
+            .heading_number_base
+            .map_or(level, |base| base.min(level));
+        self.heading_number_base = Some(base);
+        self.heading_numbers[level - 1] += 1;
+        self.heading_numbers[level..].fill(0);
+        self.heading_numbers[base - 1..level].iter().join(".")
+    }
+
+    /// Reports headings which skip a level going deeper, like an `<h4>` right
+    /// after an `<h2>`.
+    fn check_heading_level<'a>(&mut self, node: &'a AstNode<'a>, level: u8) {
+        if let Some(prev) = self.prev_heading_level {
+            if level > prev + 1 {
+                self.diagnostics.push(RenderDiagnostic {
+                    kind: RenderDiagnosticKind::SkippedHeadingLevel,
+                    sourcepos: node.data.borrow().sourcepos,
+                    message: format!("heading level jumps from h{prev} to h{level}"),
+                });
+            }
+        }
+        self.prev_heading_level = Some(level);
+    }
+
+    fn render_list_depth(&mut self, depth: usize) -> io::Result<()> {
+        if self.m2h_options.list_item_depth {
+            write!(self.output, " data-depth=\"{depth}\"")?;
+        }
+        Ok(())
+    }
+
+    /// Counts the task items of `list` itself, nested lists count on their own.
+    fn render_task_progress<'a>(&mut self, list: &'a AstNode<'a>) -> io::Result<()> {
+        if self.m2h_options.tasklist_progress {
+            let (mut total, mut done) = (0, 0);
+            for item in list.children() {
+                if let NodeValue::TaskItem(symbol) = item.data.borrow().value {
+                    total += 1;
+                    if matches!(symbol, Some('x' | 'X')) {
+                        done += 1;
+                    }
+                }
+            }
+            write!(
+                self.output,
+                " data-tasks-total=\"{total}\" data-tasks-done=\"{done}\""
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Closes the `dl-group` of a flat description list after the last `dd`
+    /// of a term.
+    fn close_flat_dl_group<'a>(&mut self, node: &'a AstNode<'a>) -> io::Result<()> {
+        if self.m2h_options.group_description_items
+            && is_in_flat_dl(node)
+            && !node.next_sibling().is_some_and(|next| {
+                matches!(next.data.borrow().value, NodeValue::DescriptionDetails)
+            })
+        {
+            self.output.write_all(b"</div>\n")?;
+        }
+        Ok(())
+    }
+
+    /// Writes text with smart punctuation and bidi isolation applied, if enabled.
+    fn write_text(&mut self, literal: &str, locale: Locale) -> io::Result<()> {
+        let text = if self.m2h_options.smart_punctuation {
+            let text = smarten(literal, self.smart_prev, locale);
+            self.smart_prev = text.chars().next_back().or(self.smart_prev);
+            Cow::Owned(text)
+        } else {
+            Cow::Borrowed(literal)
+        };
+        if self.m2h_options.bidi_isolation {
+            // All our locales are left-to-right, so only
+            // right-to-left runs need isolation.
+            for (run, rtl) in split_rtl_runs(&text) {
+                if rtl {
+                    self.output.write_all(b"<bdi>")?;
+                    self.escape(run.as_bytes())?;
+                    self.output.write_all(b"</bdi>")?;
+                } else {
+                    self.escape(run.as_bytes())?;
+                }
+            }
+        } else {
+            self.escape(text.as_bytes())?;
+        }
+        Ok(())
+    }
+
     fn render_sourcepos<'a>(&mut self, node: &'a AstNode<'a>) -> io::Result<()> {
         if self.options.render.sourcepos {
             let ast = node.data.borrow();
@@ -1287,14 +2611,211 @@ where
             let ast = node.data.borrow();
             tag_attributes.push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
         }
+        if self.m2h_options.math_alt_text {
+            tag_attributes.push(("aria-label".to_string(), literal.to_string()));
+        }
+        let equation_number = display_math.then(|| self.next_equation_number()).flatten();
+        if let Some(n) = equation_number {
+            tag_attributes.push(("id".to_string(), format!("eq-{n}")));
+        }
 
         write_opening_tag(self.output, tag, tag_attributes)?;
         self.escape(literal.as_bytes())?;
         write!(self.output, "</{}>", tag)?;
+        if let Some(n) = equation_number {
+            write!(self.output, "<span class=\"eqno\">({n})</span>")?;
+        }
 
         Ok(())
     }
 
+    /// Remembers the origin of `url` for preconnect hints, if it is external.
+    fn note_external_url(&mut self, url: &str) {
+        if !self.m2h_options.emit_preconnect_hints {
+            return;
+        }
+        if let Some(origin) = external_origin(url) {
+            if !self.external_origins.iter().any(|known| known == origin) {
+                self.external_origins.push(origin.to_string());
+            }
+        }
+    }
+
+    /// Remembers the origins of resources embedded by raw HTML, i.e. the
+    /// `src` of images, iframes and the like, and the `href` of `<link>`s.
+    fn note_external_html(&mut self, html: &str) {
+        static EMBED_URL: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(
+                r#"(?i)<(?:img|iframe|source|video|audio|script|link)\b[^>]*?\s(?:src|href)\s*=\s*["']([^"']+)"#,
+            )
+            .unwrap()
+        });
+        if !self.m2h_options.emit_preconnect_hints {
+            return;
+        }
+        for cap in EMBED_URL.captures_iter(html) {
+            self.note_external_url(&cap[1]);
+        }
+    }
+
+    /// Returns the target of the redirect `url` is the source of, if any. The
+    /// fragment is ignored and the lookup is case-insensitive.
+    fn redirect_target(&self, url: &str) -> Option<&'o str> {
+        let redirect_map = self.m2h_options.redirect_map?;
+        let url = url.split('#').next().unwrap_or_default();
+        redirect_map.get(&url.to_lowercase()).map(String::as_str)
+    }
+
+    /// Resolves a `[text](glossary:Term)` link with the `glossary_resolver`,
+    /// the term defaults to the link text. Returns `None` for other links, or
+    /// without a resolver, and `Some(None)` for terms without a glossary entry.
+    fn glossary_link<'a>(&self, node: &'a AstNode<'a>, url: &str) -> Option<Option<String>> {
+        let resolver = self.m2h_options.glossary_resolver.as_ref()?;
+        let term = url.strip_prefix("glossary:")?;
+        if term.is_empty() {
+            let mut text = Vec::with_capacity(20);
+            Self::collect_text(node, &mut text);
+            return Some(resolver(String::from_utf8_lossy(&text).trim()));
+        }
+        Some(resolver(term))
+    }
+
+    /// Loads the markup of a local `.svg` image with the `svg_loader`. Returns
+    /// `None` for other images, or if loading fails, to render an `<img>`.
+    ///
+    /// Inlined SVGs are raw HTML, so they are only inlined with `unsafe_`, and
+    /// with [`M2HOptions::sanitize`] only if it allows `<svg>`. The markup is
+    /// sanitized or tag filtered like any other raw HTML.
+    fn load_inline_svg(&self, url: &str) -> Option<String> {
+        let loader = self.m2h_options.svg_loader.as_ref()?;
+        if self.options.render.escape || !self.options.render.unsafe_ {
+            return None;
+        }
+        if let Some(config) = &self.m2h_options.sanitize {
+            if !config.tags.contains("svg") {
+                return None;
+            }
+        }
+        let path = url.split(['?', '#']).next().unwrap_or_default();
+        let is_remote = path.contains(':') || path.starts_with("//");
+        if is_remote || !path.to_ascii_lowercase().ends_with(".svg") {
+            return None;
+        }
+        let svg = loader(path).ok()?;
+        // Drop any XML declaration or doctype in front of the element.
+        let start = svg.find("<svg")?;
+        let svg = svg[start..].trim_end();
+        if let Some(config) = &self.m2h_options.sanitize {
+            return Some(sanitize_html(svg, config));
+        }
+        if self.options.extension.tagfilter {
+            let mut filtered = vec![];
+            tagfilter_block(
+                svg.as_bytes(),
+                &self.m2h_options.tagfilter_extra,
+                &mut filtered,
+            )
+            .ok()?;
+            return Some(String::from_utf8_lossy(&filtered).into_owned());
+        }
+        Some(svg.to_string())
+    }
+
+    /// Writes an inlined `svg` for the image `node`. The alt text of the
+    /// image becomes the accessible name of the `<svg>`.
+    fn write_inline_svg<'a>(&mut self, node: &'a AstNode<'a>, svg: &str) -> io::Result<()> {
+        let mut alt = Vec::with_capacity(20);
+        Self::collect_text(node, &mut alt);
+        let Some(rest) = svg.strip_prefix("<svg") else {
+            return self.output.write_all(svg.as_bytes());
+        };
+        self.output.write_all(b"<svg")?;
+        if alt.is_empty() {
+            self.output.write_all(b" aria-hidden=\"true\"")?;
+        } else {
+            self.output.write_all(b" role=\"img\" aria-label=\"")?;
+            self.escape(&alt)?;
+            self.output.write_all(b"\"")?;
+        }
+        self.output.write_all(rest.as_bytes())
+    }
+
+    /// Renders a ```` ```details Title ```` block as `<details>`, the content is
+    /// rendered as markdown. Footnotes belong to the document, so footnote
+    /// definitions in the content are dropped and references kept as text.
+    fn render_details<'a>(
+        &mut self,
+        node: &'a AstNode<'a>,
+        title: &str,
+        literal: &str,
+        locale: Locale,
+    ) -> io::Result<()> {
+        self.cr()?;
+        self.output.write_all(b"<details")?;
+        self.render_sourcepos(node)?;
+        self.output.write_all(b">\n<summary>")?;
+        self.escape(title.as_bytes())?;
+        self.output.write_all(b"</summary>\n")?;
+
+        let arena = Arena::new();
+        let root = crate::parse(&arena, literal, self.options, self.m2h_options);
+        // The content is parsed on its own, move its positions behind the
+        // opening fence.
+        let fence = node.data.borrow().sourcepos.start;
+        for descendant in root.descendants() {
+            let sourcepos = &mut descendant.data.borrow_mut().sourcepos;
+            for pos in [&mut sourcepos.start, &mut sourcepos.end] {
+                if pos.line > 0 {
+                    pos.line += fence.line;
+                    pos.column += fence.column - 1;
+                }
+            }
+        }
+        if self.m2h_options.note_cards && self.m2h_options.table_captions {
+            self.table_captions
+                .extend(take_table_captions(root, locale));
+        }
+        for descendant in root.descendants().collect::<Vec<_>>() {
+            let text = match descendant.data.borrow().value {
+                NodeValue::FootnoteDefinition(_) => None,
+                NodeValue::FootnoteReference(ref nfr) => Some(format!("[^{}]", nfr.name)),
+                _ => continue,
+            };
+            match text {
+                Some(text) => descendant.data.borrow_mut().value = NodeValue::Text(text),
+                None => descendant.detach(),
+            }
+        }
+        let depth = self.depth;
+        for child in root.children() {
+            self.format_at_depth(child, false, locale, depth + 1)?;
+        }
+        self.depth = depth;
+
+        self.cr()?;
+        self.output.write_all(b"</details>\n")?;
+        Ok(())
+    }
+
+    /// Renders a ```` ```live-example <id> ```` block as an empty placeholder
+    /// element for the frontend to hydrate.
+    fn render_live_example<'a>(&mut self, node: &'a AstNode<'a>, id: &str) -> io::Result<()> {
+        self.cr()?;
+        let tag = self
+            .m2h_options
+            .live_example_element
+            .as_deref()
+            .unwrap_or("iframe");
+        let mut attributes = vec![(String::from("data-live-example"), id.to_string())];
+        if self.options.render.sourcepos {
+            let ast = node.data.borrow();
+            attributes.push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
+        }
+        write_opening_tag(self.output, tag, attributes)?;
+        writeln!(self.output, "</{tag}>")?;
+        Ok(())
+    }
+
     // Renders a math code block, ```` ```math ```` using `<pre><code>`
     fn render_math_code_block<'a>(
         &mut self,
@@ -1322,13 +2843,33 @@ where
             let ast = node.data.borrow();
             pre_attributes.push(("data-sourcepos".to_string(), ast.sourcepos.to_string()));
         }
+        let equation_number = self.next_equation_number();
+        if let Some(n) = equation_number {
+            pre_attributes.push(("id".to_string(), format!("eq-{n}")));
+        }
 
         write_opening_tag(self.output, "pre", pre_attributes)?;
         write_opening_tag(self.output, "code", code_attributes)?;
 
+        self.output.keep_newlines = true;
         self.escape(literal.as_bytes())?;
-        self.output.write_all(b"</code></pre>\n")?;
+        self.output.keep_newlines = false;
+        self.output.write_all(b"</code>")?;
+        if let Some(n) = equation_number {
+            write!(self.output, "<span class=\"eqno\">({n})</span>")?;
+        }
+        self.output.write_all(b"</pre>\n")?;
 
         Ok(())
     }
+
+    /// Returns the number of the next display equation, if equations are
+    /// numbered.
+    fn next_equation_number(&mut self) -> Option<usize> {
+        if !self.m2h_options.number_equations {
+            return None;
+        }
+        self.equation_number += 1;
+        Some(self.equation_number)
+    }
 }
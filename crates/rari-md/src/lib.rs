@@ -1,43 +1,342 @@
+use std::collections::{HashMap, HashSet};
+
 use comrak::nodes::{AstNode, NodeValue};
 use comrak::{parse_document, Arena, ComrakOptions};
 use rari_types::locale::Locale;
 
+use crate::diagnostic::RenderDiagnostic;
 use crate::error::MarkdownError;
 use crate::p::{fix_p, is_empty_p, is_escaped_templ_p};
 
 pub mod anchor;
+pub(crate) mod bidi;
 pub(crate) mod character_set;
 pub(crate) mod ctype;
+pub mod diagnostic;
 pub(crate) mod dl;
 pub mod error;
 pub mod ext;
+#[cfg(test)]
+pub mod fixture;
 pub(crate) mod html;
 pub mod node_card;
 pub(crate) mod p;
+pub(crate) mod sanitize;
+pub(crate) mod smart;
 
 use dl::{convert_dl, is_dl};
-use html::format_document;
+use ext::wrap_highlights;
+pub use html::{
+    collect_links, count_body_words, format_document_limited, format_document_with_anchors,
+    format_document_with_external_origins, format_node_subtree, reading_time_minutes, LinkInfo,
+    LinkKind, RenderLimits, READING_WORDS_PER_MINUTE,
+};
+use html::{format_document, format_document_with_diagnostics};
+pub use sanitize::SanitizeConfig;
 
 fn iter_nodes<'a, F>(node: &'a AstNode<'a>, f: &F)
 where
     F: Fn(&'a AstNode<'a>),
 {
-    f(node);
-    for c in node.children() {
-        iter_nodes(c, f);
+    // Iterative pre-order traversal, deeply nested input must not overflow the stack.
+    // Children are looked up after `f` ran, since `f` may restructure the node.
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        f(node);
+        stack.extend(node.reverse_children());
     }
 }
 
-pub struct M2HOptions {
+/// Extra attributes for spoilers, to help the frontend reveal them on click.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SpoilerAttr {
+    /// Only `class="spoiler"`.
+    #[default]
+    None,
+    /// Make spoilers focusable, with `aria-expanded="false"` to be toggled on reveal.
+    Focusable,
+    /// A `data-spoiler` attribute.
+    Data,
+}
+
+/// How to handle the id of headings without any text.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum EmptyHeadingIds {
+    /// Anchorize the empty text, which gives `id=""`.
+    #[default]
+    Empty,
+    /// Leave out the `id` attribute.
+    Omit,
+    /// Use a `section-{n}` id, numbered like the ids of `anchor_fallback`.
+    Section,
+}
+
+pub type AnchorIdRewriter = Box<dyn Fn(&str) -> String + Send + Sync>;
+
+/// The target of a wikilink as found by a [`WikiLinkResolver`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedWikiLink {
+    pub url: String,
+    /// Emitted as the `title` of the link if present.
+    pub title: Option<String>,
+}
+
+/// Resolves the target of a wikilink. Returning `None` keeps the target as
+/// written, without a title.
+pub type WikiLinkResolver = Box<dyn Fn(&str) -> Option<ResolvedWikiLink> + Send + Sync>;
+
+/// The responsive variants of an image, as found by an [`ImageSrcset`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponsiveImage {
+    pub srcset: String,
+    pub sizes: Option<String>,
+}
+
+/// Finds the responsive variants of an image by its URL. Returning `None`
+/// renders the image with `src` only.
+pub type ImageSrcset = Box<dyn Fn(&str) -> Option<ResponsiveImage> + Send + Sync>;
+
+/// Loads the markup of a local SVG image, given its path as written in the
+/// markdown.
+pub type SvgLoader = Box<dyn Fn(&str) -> std::io::Result<String> + Send + Sync>;
+
+/// Returns the URL of the glossary entry for a term, if there is one.
+pub type GlossaryResolver = Box<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+pub struct M2HOptions<'a> {
     pub sourcepos: bool,
+    /// Wrap every `<table>` in a `<div class="table-container">` so wide tables can scroll.
+    pub table_wrapper: bool,
+    /// Report disallowed raw HTML as a [`RenderDiagnostic`] instead of omitting it silently.
+    pub error_on_raw_html: bool,
+    /// Prefix for all heading ids, to avoid collisions when embedding several documents.
+    /// Uniqueness suffixes are computed on the unprefixed id.
+    pub heading_id_prefix: Option<String>,
+    /// Maximum nesting depth of rendered nodes. Deeper nodes are dropped
+    /// and reported as a [`RenderDiagnostic`].
+    pub max_depth: usize,
+    /// Add `scope="col"` to table header cells and render the first cell of
+    /// each body row as `<th scope="row">`.
+    pub table_scopes: bool,
+    /// Mark tables as client-side sortable, `data-sortable="true"` on the
+    /// `<table>` and the column index as `data-column` on each header cell.
+    pub table_sortable: bool,
+    /// With `table_sortable`, add `data-column` to body cells as well.
+    pub table_sortable_body_cells: bool,
+    /// Render body cells of tables holding just a key combination, like
+    /// `Ctrl+C`, with every key in `<kbd>`.
+    pub kbd_in_tables: bool,
+    /// Close void elements HTML5 style (`<hr>`) instead of XHTML style (`<hr />`).
+    pub html5_void_elements: bool,
+    /// Parse `---` delimited YAML front matter and emit it as a
+    /// `<script type="application/json" data-frontmatter>` block.
+    pub emit_frontmatter_json: bool,
+    /// Shorten the visible text of autolinked bare URLs to this many
+    /// characters (without the scheme). The `href` keeps the full URL.
+    pub shorten_autolink_text: Option<usize>,
+    /// Emit the list nesting depth as `data-depth` on lists and list items.
+    pub list_item_depth: bool,
+    /// Rewrites generated heading ids (e.g. to keep legacy anchors). Ids are
+    /// made unique after rewriting.
+    pub anchor_id_rewriter: Option<AnchorIdRewriter>,
+    /// Extra attributes emitted on spoilers.
+    pub spoiler_attr: SpoilerAttr,
+    /// Prepend hierarchical section numbers (`1`, `1.1`, …) to headings, in a
+    /// `<span class="heading-number">`. Ids are still derived from the text.
+    pub auto_number_headings: bool,
+    /// Turn straight quotes into the typographic quotes of the locale, and
+    /// `--`/`---` into en and em dashes. Code and math are left alone.
+    pub smart_punctuation: bool,
+    /// Add the plain text of the footnote as `data-footnote-content` to
+    /// footnote references, so it can be shown in a popover.
+    pub footnote_popover: bool,
+    /// Emit the number of task items and checked task items as
+    /// `data-tasks-total`/`data-tasks-done` on task lists.
+    pub tasklist_progress: bool,
+    /// Wrap each `<dt>` and its `<dd>`s in a `<div class="dl-group">`.
+    pub group_description_items: bool,
+    /// Give headings without any letter or digit (e.g. only emoji) a
+    /// `section-{n}` id, numbered in document order.
+    pub anchor_fallback: bool,
+    /// Mark the first top-level paragraph, which MDN uses as the page
+    /// summary, with `data-summary`.
+    pub mark_summary_paragraph: bool,
+    /// Class added to the summary paragraph if `mark_summary_paragraph` is set.
+    pub summary_class: Option<String>,
+    /// Resolves wikilink targets to a URL and title.
+    pub wikilink_resolver: Option<WikiLinkResolver>,
+    /// Tags escaped by the tagfilter extension in addition to the default
+    /// ones (`script`, `iframe`, …).
+    pub tagfilter_extra: Vec<String>,
+    /// Render on a single line, e.g. for embedding into an attribute or JSON.
+    /// Only the newlines between tags are dropped, soft breaks become spaces
+    /// and the content of code blocks and raw HTML is kept as is.
+    pub no_newlines: bool,
+    /// The id of headings without any text.
+    pub empty_heading_ids: EmptyHeadingIds,
+    /// Add the raw LaTeX of inline math as `aria-label`, as a fallback for
+    /// screen readers until the math is rendered on the client.
+    pub math_alt_text: bool,
+    /// Treat the `id`s of raw HTML as taken when generating heading ids, so
+    /// hand-written anchors do not collide with generated ones.
+    pub reserve_html_ids: bool,
+    /// Wrap runs of right-to-left text (e.g. Arabic or Hebrew) in `<bdi>`, so
+    /// they do not garble the surrounding left-to-right text.
+    pub bidi_isolation: bool,
+    /// Render block quotes starting with a `[!NOTE]`/`[!WARNING]`/`[!CALLOUT]`
+    /// or bold `Note:` marker as note cards. When off, all block quotes are
+    /// rendered as `<blockquote>`, markers included.
+    pub note_cards: bool,
+    /// Keep the bold `Note:`/`Warning:`/`Callout:` marker of old-style note
+    /// cards in the card body instead of removing it.
+    pub keep_card_markers: bool,
+    /// Inline local `.svg` images as `<svg>` markup, so they can be styled with
+    /// CSS. Other and remote images, and SVGs failing to load, stay `<img>`.
+    /// The alt text becomes the `aria-label` of the `<svg>`. The markup is raw
+    /// HTML, so `render.unsafe_`, `tagfilter` and `sanitize` apply to it.
+    pub svg_loader: Option<SvgLoader>,
+    /// Render headings without `id` (or `data-update-id`), for fragments
+    /// embedded into other documents.
+    pub suppress_heading_ids: bool,
+    /// Render `==text==` as `<mark>text</mark>`. The text may hold other
+    /// inlines, like `==a *b*==`, but both `==` need the same parent.
+    pub highlight: bool,
+    /// Add `srcset` and `sizes` to images, as returned for their URL.
+    pub image_srcset: Option<ImageSrcset>,
+    /// Report code blocks with a language not in this set as
+    /// [`RenderDiagnosticKind::UnknownLanguage`](diagnostic::RenderDiagnosticKind::UnknownLanguage).
+    /// A `-nolint` suffix is ignored for the check.
+    pub known_languages: Option<HashSet<String>>,
+    /// Wrap text in `<span data-sourcepos>`, so every visible character maps
+    /// to its source. Needs `sourcepos` and inline sourcepos to be enabled.
+    pub text_sourcepos: bool,
+    /// Number display math (```` ```math ```` blocks and `$$...$$`) in order,
+    /// adding `id="eq-N"` and a trailing `<span class="eqno">(N)</span>`.
+    /// Inline math is never numbered.
+    pub number_equations: bool,
+    /// Write a run of consecutive soft breaks as a single one. Has no effect
+    /// with hard breaks.
+    pub collapse_soft_breaks: bool,
+    /// Redirects by lowercase source URL, like the redirects of `rari-doc`.
+    /// Links to a redirect source get the target as `data-redirected-to`, the
+    /// `href` is written as authored.
+    pub redirect_map: Option<&'a HashMap<String, String>>,
+    /// Collect the distinct external origins of images and of resources
+    /// embedded by raw HTML, and write a `<link rel="preconnect">` for each at
+    /// the top of the output.
+    pub emit_preconnect_hints: bool,
+    /// The element rendered for a ```` ```live-example <id> ```` block,
+    /// `iframe` by default. It is empty, with the id as `data-live-example`.
+    pub live_example_element: Option<String>,
+    /// Sanitize raw HTML with this allowlist instead of passing it through.
+    /// Only applies when raw HTML is rendered at all (`render.unsafe_`).
+    pub sanitize: Option<SanitizeConfig>,
+    /// Render `[text](glossary:Term)` links, or `[Term](glossary:)`, as
+    /// `<a class="glossary">` to the URL this resolves the term to, or as
+    /// plain text if the term has no glossary entry.
+    pub glossary_resolver: Option<GlossaryResolver>,
+    /// Wrap code blocks of more than this many lines in a collapsed
+    /// `<details class="code">`. Math code blocks are never collapsed.
+    pub collapse_code_over: Option<usize>,
+    /// Insert `<wbr>` after `/`, `.`, `_` and `-` in long tokens of inline
+    /// code, so they can wrap on narrow screens. Code blocks are unchanged.
+    pub inline_code_wbr: bool,
+    /// Wrap each top-level heading and its content, up to the next heading
+    /// of the same or a higher level, in `<section aria-labelledby="{id}">`.
+    pub wrap_sections: bool,
+    /// Number ordered lists alphabetically or in roman numerals with a
+    /// trailing `{type=a}` (or `A`, `i`, `I`) on the first item, rendered as
+    /// `<ol type="a">`.
+    pub list_type_hints: bool,
+    /// Follow external links with their URL in `<span class="print-url">`,
+    /// for print stylesheets to show. Internal links and autolinks, which
+    /// show their URL already, are left alone.
+    pub print_link_urls: bool,
+    /// Take a trailing `-- https://source` line of a block quote as its
+    /// source, rendered as `cite` attribute and `<footer><cite>`.
+    pub blockquote_citations: bool,
+    /// Pin the id of a heading with a trailing `{#id}`, which is removed from
+    /// the heading text. Generated ids avoid pinned ones.
+    pub custom_heading_ids: bool,
+    /// Render a block quote starting with a localized `Caption:` or
+    /// `[!CAPTION]` marker directly in front of a table as its `<caption>`.
+    pub table_captions: bool,
 }
 
-impl Default for M2HOptions {
+impl Default for M2HOptions<'_> {
     fn default() -> Self {
-        Self { sourcepos: true }
+        Self {
+            sourcepos: true,
+            table_wrapper: false,
+            error_on_raw_html: false,
+            heading_id_prefix: None,
+            max_depth: 1000,
+            table_scopes: false,
+            table_sortable: false,
+            table_sortable_body_cells: false,
+            kbd_in_tables: false,
+            html5_void_elements: false,
+            emit_frontmatter_json: false,
+            shorten_autolink_text: None,
+            list_item_depth: false,
+            anchor_id_rewriter: None,
+            spoiler_attr: SpoilerAttr::None,
+            auto_number_headings: false,
+            smart_punctuation: false,
+            footnote_popover: false,
+            tasklist_progress: false,
+            group_description_items: false,
+            anchor_fallback: false,
+            mark_summary_paragraph: false,
+            summary_class: None,
+            wikilink_resolver: None,
+            tagfilter_extra: vec![],
+            no_newlines: false,
+            empty_heading_ids: EmptyHeadingIds::Empty,
+            math_alt_text: false,
+            reserve_html_ids: false,
+            bidi_isolation: false,
+            note_cards: true,
+            keep_card_markers: false,
+            svg_loader: None,
+            suppress_heading_ids: false,
+            highlight: false,
+            image_srcset: None,
+            known_languages: None,
+            text_sourcepos: false,
+            number_equations: false,
+            collapse_soft_breaks: false,
+            redirect_map: None,
+            emit_preconnect_hints: false,
+            live_example_element: None,
+            sanitize: None,
+            glossary_resolver: None,
+            collapse_code_over: None,
+            inline_code_wbr: false,
+            wrap_sections: false,
+            list_type_hints: false,
+            print_link_urls: false,
+            blockquote_citations: false,
+            custom_heading_ids: false,
+            table_captions: false,
+        }
     }
 }
 
+/// Turns `text` into an anchor the same way heading ids are generated.
+///
+/// Unlike the anchorizer used while rendering, this does not keep track of
+/// the anchors it returned, so the same text always gives the same anchor
+/// and no `_2` style suffixes are added.
+///
+/// ```
+/// assert_eq!(rari_md::anchorize("Ticks aren't in"), "ticks_arent_in");
+/// assert_eq!(rari_md::anchorize("Ticks aren't in"), "ticks_arent_in");
+/// ```
+pub fn anchorize(text: &str) -> String {
+    anchor::anchorize(text).into_owned()
+}
+
 /// rari's custom markdown parser. This implements the MDN markdown extensions.
 /// See [MDN Markdown](https://developer.mozilla.org/en-US/docs/MDN/Writing_guidelines/Howto/Markdown_in_MDN)
 pub fn m2h(input: &str, locale: Locale) -> Result<String, MarkdownError> {
@@ -50,6 +349,50 @@ pub fn m2h_internal(
     m2h_options: M2HOptions,
 ) -> Result<String, MarkdownError> {
     let arena = Arena::new();
+    let options = comrak_options(&m2h_options);
+    let root = parse(&arena, input, &options, &m2h_options);
+
+    let mut html = vec![];
+    format_document(root, &options, &m2h_options, &mut html, locale)
+        .map_err(|_| MarkdownError::HTMLFormatError)?;
+    String::from_utf8(html).map_err(|_| MarkdownError::HTMLFormatError)
+}
+
+/// Like [`m2h_internal`] but also returns the [`RenderDiagnostic`]s collected while rendering.
+pub fn m2h_internal_with_diagnostics(
+    input: &str,
+    locale: Locale,
+    m2h_options: M2HOptions,
+) -> Result<(String, Vec<RenderDiagnostic>), MarkdownError> {
+    let arena = Arena::new();
+    let options = comrak_options(&m2h_options);
+    let root = parse(&arena, input, &options, &m2h_options);
+
+    let mut html = vec![];
+    let diagnostics =
+        format_document_with_diagnostics(root, &options, &m2h_options, &mut html, locale)
+            .map_err(|_| MarkdownError::HTMLFormatError)?;
+    let encoded_html = String::from_utf8(html).map_err(|_| MarkdownError::HTMLFormatError)?;
+    Ok((encoded_html, diagnostics))
+}
+
+/// Counts the words of the body of the markdown `input`, see [`count_body_words`].
+pub fn body_word_count(input: &str) -> usize {
+    let arena = Arena::new();
+    let options = comrak_options(&Default::default());
+    let root = parse(&arena, input, &options, &Default::default());
+    count_body_words(root)
+}
+
+/// Collects the links of the markdown `input`, see [`collect_links`].
+pub fn document_links(input: &str) -> Vec<LinkInfo> {
+    let arena = Arena::new();
+    let options = comrak_options(&Default::default());
+    let root = parse(&arena, input, &options, &Default::default());
+    collect_links(root)
+}
+
+fn comrak_options(m2h_options: &M2HOptions) -> ComrakOptions<'static> {
     let mut options = ComrakOptions::default();
     options.extension.tagfilter = false;
     options.render.sourcepos = m2h_options.sourcepos;
@@ -58,7 +401,19 @@ pub fn m2h_internal(
     options.extension.table = true;
     options.extension.autolink = true;
     options.extension.header_ids = Some(Default::default());
-    let root = parse_document(&arena, input, &options);
+    if m2h_options.emit_frontmatter_json {
+        options.extension.front_matter_delimiter = Some("---".to_string());
+    }
+    options
+}
+
+fn parse<'a>(
+    arena: &'a Arena<AstNode<'a>>,
+    input: &str,
+    options: &ComrakOptions,
+    m2h_options: &M2HOptions,
+) -> &'a AstNode<'a> {
+    let root = parse_document(arena, input, options);
 
     iter_nodes(root, &|node| {
         let (dl, templs_p, empty_p) = match node.data.borrow().value {
@@ -73,12 +428,10 @@ pub fn m2h_internal(
             fix_p(node)
         }
     });
-
-    let mut html = vec![];
-    format_document(root, &options, &mut html, locale)
-        .map_err(|_| MarkdownError::HTMLFormatError)?;
-    let encoded_html = String::from_utf8(html).map_err(|_| MarkdownError::HTMLFormatError)?;
-    Ok(encoded_html)
+    if m2h_options.highlight {
+        wrap_highlights(arena, root);
+    }
+    root
 }
 
 #[cfg(test)]
@@ -86,6 +439,8 @@ mod test {
     use html::escape_href;
 
     use super::*;
+    use crate::diagnostic::RenderDiagnosticKind;
+    use crate::error::{RenderError, RenderLimit};
 
     #[test]
     fn render_code_tags() -> Result<(), anyhow::Error> {
@@ -113,6 +468,42 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn dl_groups() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "- foo\n  - : bar\n- qux\n  - : quux",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                group_description_items: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<dl>\n<div class=\"dl-group\">\n<dt>foo</dt>\n<dd>\n<p>bar</p>\n</dd>\n</div>\n\
+            <div class=\"dl-group\">\n<dt>qux</dt>\n<dd>\n<p>quux</p>\n</dd>\n</div>\n</dl>\n"
+        );
+        // All details of a term go into its group.
+        let out = m2h_internal(
+            "- foo\n  - : bar\n  - : baz\n- qux\n  - : quux",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                group_description_items: true,
+                ..Default::default()
+            },
+        )?;
+        let (first, second) = out.split_once("</div>\n").unwrap();
+        assert!(first.starts_with("<dl>\n<div class=\"dl-group\">\n<dt>foo</dt>\n"));
+        assert_eq!(first.matches("<dd>").count(), 2);
+        assert_eq!(
+            second,
+            "<div class=\"dl-group\">\n<dt>qux</dt>\n<dd>\n<p>quux</p>\n</dd>\n</div>\n</dl>\n"
+        );
+        Ok(())
+    }
+
     #[test]
     fn dt() -> Result<(), anyhow::Error> {
         let out = m2h("- {{foo}}\n  - : bar", Locale::EnUs)?;
@@ -162,6 +553,19 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn note_attributes() -> Result<(), anyhow::Error> {
+        let out = m2h("> [!NOTE] {.compact #tips}\n> foobar", Locale::EnUs)?;
+        assert_eq!(
+            out,
+            "<div class=\"notecard note compact\" id=\"tips\" data-add-note data-sourcepos=\"1:1-2:8\">\n<p data-sourcepos=\"1:3-2:8\">\nfoobar</p>\n</div>\n"
+        );
+        let out = m2h("> [!NOTE] {.compact foo=bar}\n> foobar", Locale::EnUs)?;
+        assert!(out.starts_with("<div class=\"notecard note\" data-add-note"));
+        assert!(out.contains("{.compact foo=bar}"));
+        Ok(())
+    }
+
     #[test]
     fn note() -> Result<(), anyhow::Error> {
         let out = m2h("> **Note:** foobar", Locale::EnUs)?;
@@ -172,6 +576,1809 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn image_srcset() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "![a cat](cat.png \"Cat\") ![a dog](dog.png)",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                image_srcset: Some(Box::new(|url| {
+                    let stem = url.strip_suffix(".png").filter(|stem| *stem == "cat")?;
+                    Some(ResponsiveImage {
+                        srcset: format!("{stem}.png 1x, {stem}@2x.png 2x"),
+                        sizes: None,
+                    })
+                })),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p><img src=\"cat.png\" srcset=\"cat.png 1x, cat@2x.png 2x\" alt=\"a cat\" title=\"Cat\" /> \
+            <img src=\"dog.png\" alt=\"a dog\" /></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inline_svg() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "![a](icon.svg) ![](icon.svg) ![b](photo.png) ![c](https://example.com/icon.svg) ![d](gone.svg)",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                svg_loader: Some(svg_loader()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p><svg role=\"img\" aria-label=\"a\"><path d=\"M0 0\"/></svg> \
+            <svg aria-hidden=\"true\"><path d=\"M0 0\"/></svg> <img src=\"photo.png\" alt=\"b\" /> \
+            <img src=\"https://example.com/icon.svg\" alt=\"c\" /> <img src=\"gone.svg\" alt=\"d\" /></p>\n"
+        );
+        Ok(())
+    }
+
+    fn svg_loader() -> SvgLoader {
+        Box::new(|path| match path {
+            "icon.svg" => {
+                Ok("<?xml version=\"1.0\"?>\n<svg><path d=\"M0 0\"/></svg>\n".to_string())
+            }
+            "script.svg" => Ok("<svg><script>alert(1)</script></svg>".to_string()),
+            _ => Err(std::io::ErrorKind::NotFound.into()),
+        })
+    }
+
+    #[test]
+    fn inline_svg_raw_html_options() -> Result<(), anyhow::Error> {
+        let render = |unsafe_: bool, tagfilter: bool, sanitize: Option<SanitizeConfig>| {
+            let arena = Arena::new();
+            let mut options = ComrakOptions::default();
+            options.render.unsafe_ = unsafe_;
+            options.extension.tagfilter = tagfilter;
+            let m2h_options = M2HOptions {
+                sourcepos: false,
+                svg_loader: Some(svg_loader()),
+                sanitize,
+                ..Default::default()
+            };
+            let root = parse_document(&arena, "![a](script.svg)", &options);
+            let mut html = vec![];
+            format_document(root, &options, &m2h_options, &mut html, Locale::EnUs).unwrap();
+            String::from_utf8(html).unwrap()
+        };
+        assert_eq!(
+            render(false, false, None),
+            "<p><img src=\"script.svg\" alt=\"a\" /></p>\n"
+        );
+        assert_eq!(
+            render(true, true, None),
+            "<p><svg role=\"img\" aria-label=\"a\">&lt;script>alert(1)&lt;/script></svg></p>\n"
+        );
+        assert_eq!(
+            render(true, false, Some(Default::default())),
+            "<p><img src=\"script.svg\" alt=\"a\" /></p>\n"
+        );
+        let mut config = SanitizeConfig::default();
+        config.tags.insert("svg".to_string());
+        assert_eq!(
+            render(true, false, Some(config)),
+            "<p><svg role=\"img\" aria-label=\"a\"></svg></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn warning_marker() -> Result<(), anyhow::Error> {
+        let render = |keep_card_markers: bool| {
+            m2h_internal(
+                "> **Warning:** foobar",
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    keep_card_markers,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render(false)?,
+            "<div class=\"notecard warning\" data-add-warning>\n<p> foobar</p>\n</div>\n"
+        );
+        assert_eq!(
+            render(true)?,
+            "<div class=\"notecard warning\" data-add-warning>\n\
+            <p><strong>Warning:</strong> foobar</p>\n</div>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn note_cards_disabled() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "> [!NOTE]\n> foobar\n\n> **Warning:** foobar",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                note_cards: false,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<blockquote>\n<p>[!NOTE]\nfoobar</p>\n</blockquote>\n\
+            <blockquote>\n<p><strong>Warning:</strong> foobar</p>\n</blockquote>\n"
+        );
+        let out = m2h_internal(
+            "> [!CAPTION]\n> foobar\n\n| a |\n| - |\n| 1 |\n",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                note_cards: false,
+                table_captions: true,
+                ..Default::default()
+            },
+        )?;
+        assert!(out.starts_with(
+            "<blockquote>\n<p>[!CAPTION]\nfoobar</p>\n</blockquote>\n<table>\n<thead>"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn note_colon_spacing() -> Result<(), anyhow::Error> {
+        let out = m2h("> **Note :** foobar", Locale::EnUs)?;
+        assert!(out.starts_with("<div class=\"notecard note\" data-add-note"));
+        let out = m2h("> **Remarque :** foobar", Locale::Fr)?;
+        assert!(out.starts_with("<div class=\"callout\""));
+        let out = m2h("> **Remarque:** foobar", Locale::Fr)?;
+        assert!(out.starts_with("<div class=\"callout\""));
+        let out = m2h("> **알림 :** foobar", Locale::Ko)?;
+        assert!(out.starts_with("<div class=\"callout\""));
+        let out = m2h("> **경고:** foobar", Locale::Ko)?;
+        assert!(out.starts_with("<div class=\"notecard warning\" data-add-warning"));
+        Ok(())
+    }
+
+    #[test]
+    fn table_wrapper() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "| a |\n| - |\n| b |",
+            Locale::EnUs,
+            M2HOptions {
+                table_wrapper: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<div class=\"table-container\">\n<table data-sourcepos=\"1:1-3:5\">\n<thead>\n<tr data-sourcepos=\"1:1-1:5\">\n<th data-sourcepos=\"1:2-1:4\">a</th>\n</tr>\n</thead>\n<tbody>\n<tr data-sourcepos=\"3:1-3:5\">\n<td data-sourcepos=\"3:2-3:4\">b</td>\n</tr>\n</tbody>\n</table>\n</div>\n"
+        );
+        assert_eq!(out.matches("<div class=\"table-container\">").count(), 1);
+        assert_eq!(out.matches("</div>").count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn anchors_map() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let options = comrak_options(&M2HOptions::default());
+        let root = parse(
+            &arena,
+            "# Foo\n\nbar\n\n## Baz\n\n## Foo",
+            &options,
+            &Default::default(),
+        );
+        let mut html = vec![];
+        let m2h_options = M2HOptions {
+            heading_id_prefix: Some("h-".to_string()),
+            ..Default::default()
+        };
+        let anchors =
+            format_document_with_anchors(root, &options, &m2h_options, &mut html, Locale::EnUs)?;
+        let mut lines = anchors
+            .iter()
+            .map(|(id, sourcepos)| (id.as_str(), sourcepos.start.line))
+            .collect::<Vec<_>>();
+        lines.sort();
+        assert_eq!(lines, vec![("h-baz", 5), ("h-foo", 1), ("h-foo_2", 7)]);
+        Ok(())
+    }
+
+    #[test]
+    fn reading_time() {
+        let arena = Arena::new();
+        let options = comrak_options(&M2HOptions::default());
+        let words = vec!["word"; 300].join(" ");
+        let code = vec!["code"; 300].join("\n");
+        let input = format!("# Title\n\n{words}\n\n```js\n{code}\n```\n\n- one `two`");
+        let root = parse(&arena, &input, &options, &Default::default());
+        // 1 + 300 + 2 words, the code block is ignored.
+        assert_eq!(reading_time_minutes(root), 2);
+
+        let root = parse(&arena, "", &options, &Default::default());
+        assert_eq!(reading_time_minutes(root), 0);
+    }
+
+    #[test]
+    fn body_words() {
+        // The heading and the code block are not counted.
+        assert_eq!(
+            body_word_count("# A title\n\nOne *two* three.\n\n```js\nfour\n```\n"),
+            3
+        );
+        assert_eq!(body_word_count("## Syntax\n"), 0);
+    }
+
+    #[test]
+    fn links() {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.autolink = true;
+        options.extension.wikilinks_title_after_pipe = true;
+        let root = parse_document(
+            &arena,
+            "See [the *guide*](/en-US/docs/Guide \"Guide\"), ![A cat](cat.png)\n\
+            and [[Fetch|fetch]].\n\nMore at https://example.com.",
+            &options,
+        );
+        let links = collect_links(root);
+        let links = links
+            .iter()
+            .map(|link| {
+                let line = link.sourcepos.start.line;
+                (link.url.as_str(), link.text.as_str(), link.kind, line)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            [
+                ("/en-US/docs/Guide", "the guide", LinkKind::Link, 1),
+                ("cat.png", "A cat", LinkKind::Image, 1),
+                ("Fetch", "fetch", LinkKind::WikiLink, 2),
+                (
+                    "https://example.com",
+                    "https://example.com",
+                    LinkKind::Autolink,
+                    4
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn math_alt_text() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.math_dollars = true;
+        let root = parse_document(&arena, "Area $x^2$", &options);
+        let mut html = vec![];
+        let m2h_options = M2HOptions {
+            math_alt_text: true,
+            ..Default::default()
+        };
+        format_document(root, &options, &m2h_options, &mut html, Locale::EnUs)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<p>Area <span data-math-style=\"inline\" aria-label=\"x^2\">x^2</span></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn number_equations() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.math_dollars = true;
+        options.extension.math_code = true;
+        let root = parse_document(
+            &arena,
+            "Let $a$ be\n\n$$a^2$$\n\n```math\nb^2\n```\n",
+            &options,
+        );
+        let mut html = vec![];
+        let m2h_options = M2HOptions {
+            number_equations: true,
+            ..Default::default()
+        };
+        format_document(root, &options, &m2h_options, &mut html, Locale::EnUs)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<p>Let <span data-math-style=\"inline\">a</span> be</p>\n\
+            <p><span data-math-style=\"display\" id=\"eq-1\">a^2</span><span class=\"eqno\">(1)</span></p>\n\
+            <pre id=\"eq-2\"><code class=\"language-math\" data-math-style=\"display\">b^2\n</code><span class=\"eqno\">(2)</span></pre>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn collapse_soft_breaks() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        let render = |options: &ComrakOptions, collapse_soft_breaks: bool| {
+            let root = parse_document(&arena, "a\nb\nc\nd", options);
+            // Drop the text between the soft breaks, as a macro expanding to
+            // nothing would.
+            let paragraph = root.first_child().unwrap();
+            let children = paragraph.children().collect::<Vec<_>>();
+            children[2].detach();
+            children[4].detach();
+            let m2h_options = M2HOptions {
+                collapse_soft_breaks,
+                ..Default::default()
+            };
+            let mut html = vec![];
+            format_document(root, options, &m2h_options, &mut html, Locale::EnUs)?;
+            Ok::<_, anyhow::Error>(String::from_utf8(html)?)
+        };
+        assert_eq!(render(&options, false)?, "<p>a\n\n\nd</p>\n");
+        assert_eq!(render(&options, true)?, "<p>a\nd</p>\n");
+        options.render.hardbreaks = true;
+        assert_eq!(
+            render(&options, true)?,
+            "<p>a<br />\n<br />\n<br />\nd</p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn alert_localized_tip_title() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.alerts = true;
+        for (locale, title) in [(Locale::De, "Tipp"), (Locale::Ja, "ヒント")] {
+            let root = parse_document(&arena, "> [!TIP]\n> foobar", &options);
+            let mut html = vec![];
+            format_document(root, &options, &Default::default(), &mut html, locale)?;
+            assert_eq!(
+                String::from_utf8(html)?,
+                format!(
+                    "<div class=\"markdown-alert markdown-alert-tip\">\n\
+                    <p class=\"markdown-alert-title\">{title}</p>\n<p>foobar</p>\n</div>\n"
+                )
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn alert_localized_title() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.alerts = true;
+        let root = parse_document(&arena, "> [!WARNING]\n> foobar", &options);
+        let mut html = vec![];
+        format_document(root, &options, &Default::default(), &mut html, Locale::Fr)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<div class=\"markdown-alert markdown-alert-warning\">\n<p class=\"markdown-alert-title\">Attention</p>\n<p>foobar</p>\n</div>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn error_on_raw_html() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.render.unsafe_ = false;
+        let root = parse_document(&arena, "foo <script>bar", &options);
+        let mut html = vec![];
+        let diagnostics = format_document_with_diagnostics(
+            root,
+            &options,
+            &M2HOptions {
+                error_on_raw_html: true,
+                ..Default::default()
+            },
+            &mut html,
+            Locale::EnUs,
+        )?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<p>foo <!-- raw HTML omitted -->bar</p>\n"
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, RenderDiagnosticKind::RawHtml);
+        assert_eq!(diagnostics[0].sourcepos.start.column, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn templated_heading() -> Result<(), anyhow::Error> {
+        let (out, diagnostics) =
+            m2h_internal_with_diagnostics("# ⟬0⟭\n\n# Normal", Locale::EnUs, Default::default())?;
+        assert_eq!(
+            out,
+            "<h1 data-update-id data-sourcepos=\"1:1-1:9\">⟬0⟭</h1>\n<h1 id=\"normal\" data-sourcepos=\"3:1-3:8\">Normal</h1>\n"
+        );
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, RenderDiagnosticKind::TemplatedHeading);
+        assert_eq!(diagnostics[0].sourcepos.start.line, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn skipped_heading_level() -> Result<(), anyhow::Error> {
+        let (_, diagnostics) =
+            m2h_internal_with_diagnostics("## Foo\n\n#### Bar", Locale::EnUs, Default::default())?;
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].kind,
+            RenderDiagnosticKind::SkippedHeadingLevel
+        );
+        assert_eq!(diagnostics[0].sourcepos.start.line, 3);
+
+        let (_, diagnostics) = m2h_internal_with_diagnostics(
+            "## Foo\n\n### Bar\n\n## Baz",
+            Locale::EnUs,
+            Default::default(),
+        )?;
+        assert!(diagnostics.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_language() -> Result<(), anyhow::Error> {
+        let (out, diagnostics) = m2h_internal_with_diagnostics(
+            "```js-nolint\na\n```\n\n```javascrpt\nb\n```",
+            Locale::EnUs,
+            M2HOptions {
+                known_languages: Some(HashSet::from(["js".to_string()])),
+                ..Default::default()
+            },
+        )?;
+        assert!(out.contains("<pre class=\"brush: javascrpt notranslate\""));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, RenderDiagnosticKind::UnknownLanguage);
+        assert_eq!(diagnostics[0].sourcepos.start.line, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn subtree() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let options = comrak_options(&Default::default());
+        let root = parse(
+            &arena,
+            "# Foo\n\n- foo\n- bar\n\n# Foo",
+            &options,
+            &Default::default(),
+        );
+        let list = root
+            .children()
+            .find(|node| matches!(node.data.borrow().value, NodeValue::List(_)))
+            .unwrap();
+        let mut html = vec![];
+        format_node_subtree(list, &options, &Default::default(), &mut html, Locale::EnUs)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<ul data-sourcepos=\"3:1-5:0\">\n<li data-sourcepos=\"3:1-3:5\">foo</li>\n<li data-sourcepos=\"4:1-5:0\">bar</li>\n</ul>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn subtree_footnotes() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let m2h_options = M2HOptions {
+            sourcepos: false,
+            ..Default::default()
+        };
+        let mut options = comrak_options(&m2h_options);
+        options.extension.footnotes = true;
+        // Both documents use the label `1`, the footnote definition subtree
+        // of each is rendered as its own closed footnote section.
+        for (text, note) in [("a", "first"), ("b", "second")] {
+            let input = format!("{text}[^1]\n\n[^1]: {note}");
+            let root = parse(&arena, &input, &options, &Default::default());
+            let mut html = vec![];
+            for node in root.children() {
+                format_node_subtree(node, &options, &m2h_options, &mut html, Locale::EnUs)?;
+            }
+            assert_eq!(
+                String::from_utf8(html)?,
+                format!(
+                    "<p>{text}<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\" data-footnote-ref>1</a></sup></p>\n\
+                    <section class=\"footnotes\" data-footnotes>\n<ol>\n<li id=\"fn-1\">\n\
+                    <p>{note} <a href=\"#fnref-1\" class=\"footnote-backref\" data-footnote-backref data-footnote-backref-idx=\"1\" aria-label=\"Back to reference 1\">↩</a></p>\n\
+                    </li>\n</ol>\n</section>\n"
+                )
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn task_list_symbols() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.tasklist = true;
+        options.parse.relaxed_tasklist_matching = true;
+        let root = parse_document(
+            &arena,
+            "- [x] foo\n- [-] bar\n- [ ] baz\n- [?] qux",
+            &options,
+        );
+        let mut html = vec![];
+        format_document(root, &options, &Default::default(), &mut html, Locale::EnUs)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<ul>\n\
+            <li><input type=\"checkbox\" checked=\"\" disabled=\"\" /> foo</li>\n\
+            <li><input type=\"checkbox\" data-indeterminate disabled=\"\" /> bar</li>\n\
+            <li><input type=\"checkbox\" disabled=\"\" /> baz</li>\n\
+            <li><input type=\"checkbox\" disabled=\"\" /> qux</li>\n\
+            </ul>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tasklist_progress() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.tasklist = true;
+        let m2h_options = M2HOptions {
+            tasklist_progress: true,
+            ..Default::default()
+        };
+        let root = parse_document(
+            &arena,
+            "- [x] foo\n- [ ] bar\n  - [ ] nested\n- [X] baz",
+            &options,
+        );
+        let mut html = vec![];
+        format_document(root, &options, &m2h_options, &mut html, Locale::EnUs)?;
+        let html = String::from_utf8(html)?;
+        assert!(html.starts_with("<ul data-tasks-total=\"3\" data-tasks-done=\"2\">\n"));
+        assert!(html.contains("<ul data-tasks-total=\"1\" data-tasks-done=\"0\">\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn footnote_reference_sourcepos() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.footnotes = true;
+        options.render.sourcepos = true;
+        options.render.experimental_inline_sourcepos = true;
+        let root = parse_document(&arena, "Foo[^1] bar[^1]\n\n[^1]: Note", &options);
+        let mut html = vec![];
+        format_document(root, &options, &Default::default(), &mut html, Locale::EnUs)?;
+        let html = String::from_utf8(html)?;
+        assert!(html.starts_with(
+            "<p data-sourcepos=\"1:1-1:15\">Foo\
+            <sup data-sourcepos=\"1:4-1:7\" class=\"footnote-ref\">\
+            <a href=\"#fn-1\" id=\"fnref-1\" data-footnote-ref>1</a></sup> bar\
+            <sup data-sourcepos=\"1:12-1:15\" class=\"footnote-ref\">\
+            <a href=\"#fn-1\" id=\"fnref-1-2\" data-footnote-ref>1</a></sup></p>\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn footnote_popover() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.footnotes = true;
+        let m2h_options = M2HOptions {
+            footnote_popover: true,
+            ..Default::default()
+        };
+        let root = parse_document(&arena, "Foo[^1]\n\n[^1]: A *\"short\"* `note`.", &options);
+        let mut html = vec![];
+        format_document(root, &options, &m2h_options, &mut html, Locale::EnUs)?;
+        let html = String::from_utf8(html)?;
+        assert!(html.starts_with(
+            "<p>Foo<sup class=\"footnote-ref\"><a href=\"#fn-1\" id=\"fnref-1\" \
+            data-footnote-ref data-footnote-content=\"A &quot;short&quot; note.\">1</a></sup></p>\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn anchor_fallback() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "## 概要\n\n## 概要\n\n## 🎉🎉\n\n## !!!\n\n## 🚀",
+            Locale::Ja,
+            M2HOptions {
+                sourcepos: false,
+                anchor_fallback: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<h2 id=\"概要\">概要</h2>\n<h2 id=\"概要_2\">概要</h2>\n\
+            <h2 id=\"section-1\">🎉🎉</h2>\n<h2 id=\"section-2\">!!!</h2>\n\
+            <h2 id=\"section-3\">🚀</h2>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bidi_isolation() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "Hello is مرحبا بالعالم (world) in *Arabic*.",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                bidi_isolation: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p>Hello is <bdi>مرحبا بالعالم</bdi> (world) in <em>Arabic</em>.</p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reserve_html_ids() -> Result<(), anyhow::Error> {
+        let render = |reserve_html_ids: bool| {
+            m2h_internal(
+                "<div id=\"intro\"></div>\n\n## Intro",
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    reserve_html_ids,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render(false)?,
+            "<div id=\"intro\"></div>\n<h2 id=\"intro\">Intro</h2>\n"
+        );
+        assert_eq!(
+            render(true)?,
+            "<div id=\"intro\"></div>\n<h2 id=\"intro_2\">Intro</h2>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn highlight() -> Result<(), anyhow::Error> {
+        let render = |input: &str, sourcepos: bool| {
+            m2h_internal(
+                input,
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos,
+                    highlight: true,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render("a ==x < y & z== word", false)?,
+            "<p>a <mark>x &lt; y &amp; z</mark> word</p>\n"
+        );
+        assert_eq!(
+            render("foo ==bar==", true)?,
+            "<p data-sourcepos=\"1:1-1:11\">foo <mark data-sourcepos=\"1:5-1:11\">bar</mark></p>\n"
+        );
+        assert_eq!(
+            render("a == b, ==c and d=== e", false)?,
+            "<p>a == b, ==c and d=== e</p>\n"
+        );
+        // Highlights hold other inlines.
+        assert_eq!(
+            render("==a *b*== c ==`d` [e](/f)== ==g==", false)?,
+            "<p><mark>a <em>b</em></mark> c <mark><code>d</code> <a href=\"/f\">e</a></mark> \
+            <mark>g</mark></p>\n"
+        );
+        assert_eq!(
+            render("x ==*a*==", true)?,
+            "<p data-sourcepos=\"1:1-1:9\">x <mark data-sourcepos=\"1:3-1:9\">\
+            <em data-sourcepos=\"1:5-1:7\">a</em></mark></p>\n"
+        );
+        // Both delimiters need the same parent.
+        assert_eq!(
+            render("*==a*== ==b\nc==", false)?,
+            "<p><em>==a</em>== <mark>b\nc</mark></p>\n"
+        );
+        assert_eq!(
+            render("== a== ==a *b ==c*", false)?,
+            "<p>== a== ==a <em>b ==c</em></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn text_sourcepos() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.render.sourcepos = true;
+        let render = |options: &ComrakOptions, text_sourcepos: bool| {
+            let root = parse_document(&arena, "foo *bar*", options);
+            let m2h_options = M2HOptions {
+                text_sourcepos,
+                ..Default::default()
+            };
+            let mut html = vec![];
+            format_document(root, options, &m2h_options, &mut html, Locale::EnUs)?;
+            Ok::<_, anyhow::Error>(String::from_utf8(html)?)
+        };
+        let plain = "<p data-sourcepos=\"1:1-1:9\">foo <em>bar</em></p>\n";
+        assert_eq!(render(&options, false)?, plain);
+        assert_eq!(render(&options, true)?, plain);
+        options.render.experimental_inline_sourcepos = true;
+        assert_eq!(
+            render(&options, false)?,
+            "<p data-sourcepos=\"1:1-1:9\">foo <em data-sourcepos=\"1:5-1:9\">bar</em></p>\n"
+        );
+        assert_eq!(
+            render(&options, true)?,
+            "<p data-sourcepos=\"1:1-1:9\"><span data-sourcepos=\"1:1-1:4\">foo </span>\
+            <em data-sourcepos=\"1:5-1:9\"><span data-sourcepos=\"1:6-1:8\">bar</span></em></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn suppress_heading_ids() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "# Foo\n\n## {{Bar}}",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                suppress_heading_ids: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(out, "<h1>Foo</h1>\n<h2>{{Bar}}</h2>\n");
+        Ok(())
+    }
+
+    #[test]
+    fn empty_heading_ids() -> Result<(), anyhow::Error> {
+        let render = |empty_heading_ids: EmptyHeadingIds| {
+            m2h_internal(
+                "## Foo\n\n##\n\n## 🚀",
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    anchor_fallback: true,
+                    empty_heading_ids,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render(EmptyHeadingIds::Omit)?,
+            "<h2 id=\"foo\">Foo</h2>\n<h2></h2>\n<h2 id=\"section-1\">🚀</h2>\n"
+        );
+        assert_eq!(
+            render(EmptyHeadingIds::Section)?,
+            "<h2 id=\"foo\">Foo</h2>\n<h2 id=\"section-1\"></h2>\n\
+            <h2 id=\"section-2\">🚀</h2>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn mark_summary_paragraph() -> Result<(), anyhow::Error> {
+        let render = |input: &str, summary_class: Option<&str>| {
+            m2h_internal(
+                input,
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    mark_summary_paragraph: true,
+                    summary_class: summary_class.map(String::from),
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render("## Foo\n\nSummary\n\nMore", None)?,
+            "<h2 id=\"foo\">Foo</h2>\n<p data-summary>Summary</p>\n<p>More</p>\n"
+        );
+        assert_eq!(
+            render("> Quote\n\n- item\n\n  para\n\nSummary", Some("summary"))?,
+            "<blockquote>\n<p>Quote</p>\n</blockquote>\n<ul>\n<li>\n<p>item</p>\n<p>para</p>\n</li>\n</ul>\n\
+            <p class=\"summary\" data-summary>Summary</p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn render_limits() -> Result<(), anyhow::Error> {
+        let render = |limits: RenderLimits| {
+            let arena = Arena::new();
+            let options = ComrakOptions::default();
+            let root = parse_document(&arena, "Foo *bar*\n\nBaz", &options);
+            let mut html = vec![];
+            let result = format_document_limited(
+                root,
+                &options,
+                &Default::default(),
+                &mut html,
+                Locale::EnUs,
+                &limits,
+            );
+            (String::from_utf8(html).unwrap(), result)
+        };
+        let (out, result) = render(RenderLimits::default());
+        assert!(result.is_ok());
+        assert_eq!(out, "<p>Foo <em>bar</em></p>\n<p>Baz</p>\n");
+
+        let (out, result) = render(RenderLimits {
+            max_nodes: Some(4),
+            ..Default::default()
+        });
+        assert!(matches!(
+            result,
+            Err(RenderError::LimitExceeded(RenderLimit::Nodes))
+        ));
+        assert_eq!(out, "<p>Foo <em>");
+
+        let (out, result) = render(RenderLimits {
+            max_output_bytes: Some(10),
+            ..Default::default()
+        });
+        assert!(matches!(
+            result,
+            Err(RenderError::LimitExceeded(RenderLimit::OutputBytes))
+        ));
+        assert_eq!(out, "<p>Foo <em");
+        Ok(())
+    }
+
+    #[test]
+    fn wikilink_title() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.extension.wikilinks_title_after_pipe = true;
+        let m2h_options = M2HOptions {
+            sourcepos: false,
+            wikilink_resolver: Some(Box::new(|target| {
+                (target == "Fetch").then(|| ResolvedWikiLink {
+                    url: "/en-US/docs/Web/API/Fetch_API".to_string(),
+                    title: Some("Fetch API <\"guide\">".to_string()),
+                })
+            })),
+            ..Default::default()
+        };
+        let root = parse_document(&arena, "[[Fetch|fetch]] [[Other]]", &options);
+        let mut html = vec![];
+        format_document(root, &options, &m2h_options, &mut html, Locale::EnUs)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<p><a href=\"/en-US/docs/Web/API/Fetch_API\" title=\"Fetch API &lt;&quot;guide&quot;&gt;\" data-wikilink=\"true\">fetch</a> \
+            <a href=\"Other\" data-wikilink=\"true\">Other</a></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn tagfilter_extra() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.render.unsafe_ = true;
+        options.extension.tagfilter = true;
+        let m2h_options = M2HOptions {
+            sourcepos: false,
+            tagfilter_extra: vec!["OBJECT".to_string()],
+            ..Default::default()
+        };
+        let root = parse_document(
+            &arena,
+            "a <object data=\"x\"></object> <objects> <b>b</b> <script>",
+            &options,
+        );
+        let mut html = vec![];
+        format_document(root, &options, &m2h_options, &mut html, Locale::EnUs)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<p>a &lt;object data=\"x\">&lt;/object> <objects> <b>b</b> &lt;script></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_newlines() -> Result<(), anyhow::Error> {
+        let input = "- foo\n  bar\n- baz\n\n```js\na;\nb;\n```";
+        let render = |no_newlines: bool| {
+            m2h_internal(
+                input,
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    no_newlines,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render(false)?,
+            "<ul>\n<li>foo\nbar</li>\n<li>baz</li>\n</ul>\n\
+            <pre class=\"brush: js notranslate\">a;\nb;\n</pre>\n"
+        );
+        assert_eq!(
+            render(true)?,
+            "<ul><li>foo bar</li><li>baz</li></ul>\
+            <pre class=\"brush: js notranslate\">a;\nb;\n</pre>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn heading_id_prefix() -> Result<(), anyhow::Error> {
+        let render = |prefix: &str| {
+            m2h_internal(
+                "## Foo\n\n## Foo",
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    heading_id_prefix: Some(prefix.to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+        let a = render("a-")?;
+        let b = render("b-")?;
+        assert_eq!(
+            a,
+            "<h2 id=\"a-foo\">Foo</h2>\n<h2 id=\"a-foo_2\">Foo</h2>\n"
+        );
+        assert_eq!(
+            b,
+            "<h2 id=\"b-foo\">Foo</h2>\n<h2 id=\"b-foo_2\">Foo</h2>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn custom_heading_ids() -> Result<(), anyhow::Error> {
+        let render = |input: &str| {
+            m2h_internal(
+                input,
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    custom_heading_ids: true,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render("## Some *title* {#custom-id}\n\n## Other{#x.y_z}")?,
+            "<h2 id=\"custom-id\">Some <em>title</em></h2>\n<h2 id=\"x.y_z\">Other</h2>\n"
+        );
+        // Generated ids avoid pinned ones, wherever those come.
+        assert_eq!(
+            render("## Foo\n\n## Bar {#foo}\n\n## Foo")?,
+            "<h2 id=\"foo_2\">Foo</h2>\n<h2 id=\"foo\">Bar</h2>\n<h2 id=\"foo_3\">Foo</h2>\n"
+        );
+        // Only a trailing `{#id}` pins the id.
+        assert_eq!(
+            render("## The {#id} attribute\n\n## Broken {#}")?,
+            "<h2 id=\"the_id_attribute\">The {#id} attribute</h2>\n<h2 id=\"broken\">Broken {#}</h2>\n"
+        );
+        // A pinned id taken already is made unique.
+        assert_eq!(
+            render("## Bar {#foo}\n\n## Baz {#foo}\n\n## Foo")?,
+            "<h2 id=\"foo\">Bar</h2>\n<h2 id=\"foo_2\">Baz</h2>\n<h2 id=\"foo_3\">Foo</h2>\n"
+        );
+        // Off by default.
+        assert_eq!(
+            m2h_internal(
+                "## Bar {#foo}",
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    ..Default::default()
+                },
+            )?,
+            "<h2 id=\"bar_foo\">Bar {#foo}</h2>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn code_nolint() -> Result<(), anyhow::Error> {
+        let out = m2h("```js-nolint\nfoo\n```", Locale::EnUs)?;
+        assert!(out.starts_with("<pre "));
+        assert!(out.ends_with(">foo\n</pre>\n"));
+        assert!(out.contains(" class=\"brush: js notranslate\""));
+        assert!(out.contains(" data-nolint=\"true\""));
+        assert!(out.contains(" data-nolint-langs=\"js\""));
+
+        let out = m2h("```js\nfoo\n```", Locale::EnUs)?;
+        assert!(!out.contains("data-nolint"));
+        Ok(())
+    }
+
+    #[test]
+    fn code_attribute_order() -> Result<(), anyhow::Error> {
+        let arena = Arena::new();
+        let mut options = ComrakOptions::default();
+        options.render.sourcepos = true;
+        options.render.full_info_string = true;
+        let root = parse_document(&arena, "```js-nolint example-good\nfoo\n```", &options);
+        let mut html = vec![];
+        format_document(root, &options, &Default::default(), &mut html, Locale::EnUs)?;
+        assert_eq!(
+            String::from_utf8(html)?,
+            "<pre class=\"brush: js example-good notranslate\" data-sourcepos=\"1:1-3:3\" data-meta=\"example-good\" data-nolint=\"true\" data-nolint-langs=\"js\">foo\n</pre>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deeply_nested() -> Result<(), anyhow::Error> {
+        let input = format!("{}foo", ">".repeat(100_000));
+        let (out, diagnostics) = m2h_internal_with_diagnostics(
+            &input,
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(out.matches("<blockquote>").count(), 1000);
+        assert_eq!(out.matches("</blockquote>").count(), 1000);
+        assert!(!out.contains("foo"));
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, RenderDiagnosticKind::MaxDepthExceeded);
+
+        let (out, diagnostics) = m2h_internal_with_diagnostics(
+            "> ```details Deep\n> > > foo\n> ```\n",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                max_depth: 3,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(out.matches("<blockquote>").count(), 2);
+        assert!(!out.contains("foo"));
+        assert_eq!(diagnostics.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn table_scopes() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "| a | b |\n| - | :-: |\n| c | d |",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                table_scopes: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<table>\n<thead>\n<tr>\n<th scope=\"col\">a</th>\n<th scope=\"col\" align=\"center\">b</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<th scope=\"row\">c</th>\n<td align=\"center\">d</td>\n</tr>\n</tbody>\n</table>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn table_sortable() -> Result<(), anyhow::Error> {
+        let render = |table_sortable_body_cells| {
+            m2h_internal(
+                "| a | b |\n| - | - |\n| c | d |",
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    table_sortable: true,
+                    table_sortable_body_cells,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render(false)?,
+            "<table data-sortable=\"true\">\n<thead>\n<tr>\n<th data-column=\"0\">a</th>\n<th data-column=\"1\">b</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>c</td>\n<td>d</td>\n</tr>\n</tbody>\n</table>\n"
+        );
+        assert_eq!(
+            render(true)?,
+            "<table data-sortable=\"true\">\n<thead>\n<tr>\n<th data-column=\"0\">a</th>\n<th data-column=\"1\">b</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td data-column=\"0\">c</td>\n<td data-column=\"1\">d</td>\n</tr>\n</tbody>\n</table>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn kbd_in_tables() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "| Ctrl+C | Action |\n| - | - |\n| Ctrl+C | Copy |\n| Cmd + Shift + Z | Redo |\n| 1+1 | Ctrl+*C* |",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                kbd_in_tables: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<table>\n<thead>\n<tr>\n<th>Ctrl+C</th>\n<th>Action</th>\n</tr>\n</thead>\n<tbody>\n\
+            <tr>\n<td><kbd>Ctrl</kbd>+<kbd>C</kbd></td>\n<td>Copy</td>\n</tr>\n\
+            <tr>\n<td><kbd>Cmd</kbd>+<kbd>Shift</kbd>+<kbd>Z</kbd></td>\n<td>Redo</td>\n</tr>\n\
+            <tr>\n<td>1+1</td>\n<td>Ctrl+<em>C</em></td>\n</tr>\n</tbody>\n</table>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn void_elements() -> Result<(), anyhow::Error> {
+        let render = |input: &str, html5_void_elements: bool| {
+            let arena = Arena::new();
+            let mut options = ComrakOptions::default();
+            options.render.hardbreaks = true;
+            let root = parse_document(&arena, input, &options);
+            let mut html = vec![];
+            format_document(
+                root,
+                &options,
+                &M2HOptions {
+                    html5_void_elements,
+                    ..Default::default()
+                },
+                &mut html,
+                Locale::EnUs,
+            )
+            .unwrap();
+            String::from_utf8(html).unwrap()
+        };
+        assert_eq!(render("---", false), "<hr />\n");
+        assert_eq!(render("---", true), "<hr>\n");
+        assert_eq!(render("a\\\nb", false), "<p>a<br />\nb</p>\n");
+        assert_eq!(render("a\\\nb", true), "<p>a<br>\nb</p>\n");
+        assert_eq!(render("a\nb", false), "<p>a<br />\nb</p>\n");
+        assert_eq!(render("a\nb", true), "<p>a<br>\nb</p>\n");
+        assert_eq!(
+            render("![a](/b.png)", false),
+            "<p><img src=\"/b.png\" alt=\"a\" /></p>\n"
+        );
+        assert_eq!(
+            render("![a](/b.png)", true),
+            "<p><img src=\"/b.png\" alt=\"a\"></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn shorten_autolink_text() -> Result<(), anyhow::Error> {
+        let options = || M2HOptions {
+            sourcepos: false,
+            shorten_autolink_text: Some(20),
+            ..Default::default()
+        };
+        let url = "https://developer.mozilla.org/en-US/docs/Web/API/Element";
+        let out = m2h_internal(url, Locale::EnUs, options())?;
+        assert_eq!(
+            out,
+            format!("<p><a href=\"{url}\" data-autolink=\"\">developer.mozilla.or…</a></p>\n")
+        );
+        let out = m2h_internal("https://example.com", Locale::EnUs, options())?;
+        assert_eq!(
+            out,
+            "<p><a href=\"https://example.com\" data-autolink=\"\">example.com</a></p>\n"
+        );
+        let out = m2h_internal("[link](https://example.com)", Locale::EnUs, options())?;
+        assert_eq!(out, "<p><a href=\"https://example.com\">link</a></p>\n");
+        Ok(())
+    }
+
+    #[test]
+    fn blockquote_citation() -> Result<(), anyhow::Error> {
+        let options = || M2HOptions {
+            sourcepos: false,
+            blockquote_citations: true,
+            ..Default::default()
+        };
+        let input = "> To be or not to be.\n> -- https://example.com/hamlet?act=3&scene=1";
+        let out = m2h_internal(input, Locale::EnUs, options())?;
+        assert_eq!(
+            out,
+            "<blockquote cite=\"https://example.com/hamlet?act=3&amp;scene=1\">\n<p>To be or not to be.</p>\n<footer><cite>https://example.com/hamlet?act=3&amp;scene=1</cite></footer>\n</blockquote>\n"
+        );
+        let out = m2h_internal(
+            "> To be or not to be.\n> -- Hamlet",
+            Locale::EnUs,
+            options(),
+        )?;
+        assert_eq!(
+            out,
+            "<blockquote>\n<p>To be or not to be.\n-- Hamlet</p>\n</blockquote>\n"
+        );
+        // Nothing would remain but the citation.
+        let out = m2h_internal("> -- https://example.com/hamlet", Locale::EnUs, options())?;
+        assert_eq!(
+            out,
+            "<blockquote>\n<p>-- <a href=\"https://example.com/hamlet\" data-autolink=\"\">https://example.com/hamlet</a></p>\n</blockquote>\n"
+        );
+        // Off by default.
+        let out = m2h_internal(
+            input,
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<blockquote>\n<p>To be or not to be.\n-- <a href=\"https://example.com/hamlet?act=3&amp;scene=1\" data-autolink=\"\">https://example.com/hamlet?act=3&amp;scene=1</a></p>\n</blockquote>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn redirect_map() -> Result<(), anyhow::Error> {
+        let options = |redirect_map| M2HOptions {
+            sourcepos: false,
+            redirect_map,
+            ..Default::default()
+        };
+        let redirect_map = HashMap::from([(
+            "/en-us/docs/web/api/old".to_string(),
+            "/en-US/docs/Web/API/New".to_string(),
+        )]);
+        let input = "[old](/en-US/docs/Web/API/Old#usage) [other](/en-US/docs/Web/API/Other)";
+        let out = m2h_internal(input, Locale::EnUs, options(Some(&redirect_map)))?;
+        assert_eq!(
+            out,
+            "<p><a href=\"/en-US/docs/Web/API/Old#usage\" data-redirected-to=\"/en-US/docs/Web/API/New\">old</a> \
+            <a href=\"/en-US/docs/Web/API/Other\">other</a></p>\n"
+        );
+        let out = m2h_internal(input, Locale::EnUs, options(None))?;
+        assert_eq!(
+            out,
+            "<p><a href=\"/en-US/docs/Web/API/Old#usage\">old</a> \
+            <a href=\"/en-US/docs/Web/API/Other\">other</a></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn preconnect_hints() -> Result<(), anyhow::Error> {
+        let input =
+            "![a](https://cdn.example.com/a.png) ![b](https://cdn.example.com/img/b.png?w=2)\n\n\
+            <iframe src=\"https://embed.example.org/live\"></iframe>\n\n\
+            ![c](/en-US/docs/c.png) [d](https://developer.mozilla.org/)";
+        let options = || M2HOptions {
+            sourcepos: false,
+            emit_preconnect_hints: true,
+            ..Default::default()
+        };
+        let hints = "<link rel=\"preconnect\" href=\"https://cdn.example.com\">\n\
+            <link rel=\"preconnect\" href=\"https://embed.example.org\">\n<p>";
+        let out = m2h_internal(input, Locale::EnUs, options())?;
+        assert!(out.starts_with(hints));
+        assert_eq!(out.matches("rel=\"preconnect\"").count(), 2);
+        // Every entry point returning diagnostics writes the hints as well.
+        let (out, _) = m2h_internal_with_diagnostics(input, Locale::EnUs, options())?;
+        assert!(out.starts_with(hints));
+        let arena = Arena::new();
+        let comrak_options = comrak_options(&options());
+        let root = parse(&arena, input, &comrak_options, &options());
+        let mut html = vec![];
+        format_document_limited(
+            root,
+            &comrak_options,
+            &options(),
+            &mut html,
+            Locale::EnUs,
+            &Default::default(),
+        )?;
+        assert!(String::from_utf8(html)?.starts_with(hints));
+        Ok(())
+    }
+
+    #[test]
+    fn live_example() -> Result<(), anyhow::Error> {
+        let options = |live_example_element| M2HOptions {
+            sourcepos: false,
+            live_example_element,
+            ..Default::default()
+        };
+        let out = m2h_internal(
+            "```live-example css-border\n```\n",
+            Locale::EnUs,
+            options(None),
+        )?;
+        assert_eq!(out, "<iframe data-live-example=\"css-border\"></iframe>\n");
+        let out = m2h_internal(
+            "```live-example css-border\n```\n",
+            Locale::EnUs,
+            options(Some("interactive-example".to_string())),
+        )?;
+        assert_eq!(
+            out,
+            "<interactive-example data-live-example=\"css-border\"></interactive-example>\n"
+        );
+        // Without a valid id, this is a code block.
+        let out = m2h_internal("```live-example\nfoo\n```\n", Locale::EnUs, options(None))?;
+        assert_eq!(
+            out,
+            "<pre class=\"brush: live-example notranslate\">foo\n</pre>\n"
+        );
+        let out = m2h_internal(
+            "```live-example \"><script>\nfoo\n```\n",
+            Locale::EnUs,
+            options(None),
+        )?;
+        assert!(out.starts_with("<pre class=\"brush: live-example"));
+        Ok(())
+    }
+
+    #[test]
+    fn sanitize() -> Result<(), anyhow::Error> {
+        let input = "Press <kbd onclick=\"steal()\">Ctrl</kbd>\n\n<script>steal()</script>\n";
+        let out = m2h_internal(
+            input,
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                sanitize: Some(SanitizeConfig::default()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(out, "<p>Press <kbd>Ctrl</kbd></p>\n\n");
+        let out = m2h_internal(
+            "<!--><script>alert(1)</script>-->\n",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                sanitize: Some(SanitizeConfig::default()),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(out, "--&gt;\n");
+        let out = m2h_internal(
+            input,
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p>Press <kbd onclick=\"steal()\">Ctrl</kbd></p>\n<script>steal()</script>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn glossary_links() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "A [CORS](glossary:) request to [an origin](glossary:Origin), [the foo](glossary:Foo) and [x](glossary:CORS).",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                glossary_resolver: Some(Box::new(|term| match term {
+                    "CORS" | "Origin" => Some(format!("/en-US/docs/Glossary/{term}")),
+                    _ => None,
+                })),
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p>A <a class=\"glossary\" href=\"/en-US/docs/Glossary/CORS\">CORS</a> request to \
+            <a class=\"glossary\" href=\"/en-US/docs/Glossary/Origin\">an origin</a>, the foo and \
+            <a class=\"glossary\" href=\"/en-US/docs/Glossary/CORS\">x</a>.</p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn collapse_code() -> Result<(), anyhow::Error> {
+        let long = (1..=50)
+            .map(|i| format!("let x{i} = {i};\n"))
+            .collect::<String>();
+        let input = format!("```js\n{long}```\n\n```js\nlet y = 1;\n```\n\n```math\n{long}```\n");
+        let out = m2h_internal(
+            &input,
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                collapse_code_over: Some(20),
+                ..Default::default()
+            },
+        )?;
+        assert!(out.starts_with(&format!(
+            "<details class=\"code\"><summary>Show code</summary>\n\
+            <pre class=\"brush: js notranslate\">{long}</pre>\n</details>\n\
+            <pre class=\"brush: js notranslate\">let y = 1;\n</pre>\n"
+        )));
+        assert_eq!(out.matches("<details").count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn inline_code_wbr() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "See `/en-US/docs/Web/API/Window/scroll_event` or `a/b.c`.\n\n```\n/en-US/docs/Web/API/Window\n```",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                inline_code_wbr: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p>See <code>/<wbr>en-<wbr>US/<wbr>docs/<wbr>Web/<wbr>API/<wbr>Window/<wbr>scroll_<wbr>event</code> or <code>a/b.c</code>.</p>\n\
+            <pre class=\"notranslate\">/en-US/docs/Web/API/Window\n</pre>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn wrap_sections() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "Intro\n\n## A\n\na\n\n### A.1\n\na.1\n\n## B\n\n> ## Quoted\n\nb",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                wrap_sections: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p>Intro</p>\n\
+            <section aria-labelledby=\"a\">\n<h2 id=\"a\">A</h2>\n<p>a</p>\n\
+            <section aria-labelledby=\"a.1\">\n<h3 id=\"a.1\">A.1</h3>\n<p>a.1</p>\n\
+            </section>\n</section>\n\
+            <section aria-labelledby=\"b\">\n<h2 id=\"b\">B</h2>\n\
+            <blockquote>\n<h2 id=\"quoted\">Quoted</h2>\n</blockquote>\n<p>b</p>\n\
+            </section>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn list_type_hints() -> Result<(), anyhow::Error> {
+        let render = |input: &str| {
+            m2h_internal(
+                input,
+                Locale::EnUs,
+                M2HOptions {
+                    sourcepos: false,
+                    list_type_hints: true,
+                    ..Default::default()
+                },
+            )
+        };
+        assert_eq!(
+            render("1. First {type=a}\n2. Second")?,
+            "<ol type=\"a\">\n<li>First</li>\n<li>Second</li>\n</ol>\n"
+        );
+        assert_eq!(
+            render("3. Third {type=I}")?,
+            "<ol type=\"I\" start=\"3\">\n<li>Third</li>\n</ol>\n"
+        );
+        assert_eq!(
+            render("1. First {type=x}\n- Item {type=a}")?,
+            "<ol>\n<li>First {type=x}</li>\n</ol>\n<ul>\n<li>Item {type=a}</li>\n</ul>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn print_link_urls() -> Result<(), anyhow::Error> {
+        let out = m2h_internal(
+            "[Spec](https://example.com/spec?a=1&b=2), [guide](/en-US/docs/Guide), www.example.com and https://example.com",
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                print_link_urls: true,
+                ..Default::default()
+            },
+        )?;
+        assert_eq!(
+            out,
+            "<p><a href=\"https://example.com/spec?a=1&amp;b=2\">Spec</a>\
+            <span class=\"print-url\">(https://example.com/spec?a=1&amp;b=2)</span>, \
+            <a href=\"/en-US/docs/Guide\">guide</a>, \
+            <a href=\"http://www.example.com\">www.example.com</a> and \
+            <a href=\"https://example.com\" data-autolink=\"\">https://example.com</a></p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn table_caption() -> Result<(), anyhow::Error> {
+        let options = || M2HOptions {
+            sourcepos: false,
+            table_captions: true,
+            ..Default::default()
+        };
+        let table = "| a | b |\n| - | - |\n| 1 | 2 |\n";
+        let expected = |caption: &str| {
+            format!(
+                "<table>\n<caption>{caption}</caption>\n<thead>\n<tr>\n<th>a</th>\n<th>b</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>1</td>\n<td>2</td>\n</tr>\n</tbody>\n</table>\n"
+            )
+        };
+        let out = m2h_internal(
+            &format!("> **Caption:** Browser <em>support</em>\n\n{table}"),
+            Locale::EnUs,
+            options(),
+        )?;
+        assert_eq!(out, expected("Browser <em>support</em>"));
+        let out = m2h_internal(
+            &format!("> [!CAPTION]\n> Browser support\n\n{table}"),
+            Locale::EnUs,
+            options(),
+        )?;
+        assert_eq!(out, expected("Browser support"));
+        let out = m2h_internal(
+            &format!("> **说明：** 浏览器支持\n\n{table}"),
+            Locale::ZhCn,
+            options(),
+        )?;
+        assert_eq!(out, expected("浏览器支持"));
+        let out = m2h_internal(
+            &format!("> **說明：** 瀏覽器支援\n\n{table}"),
+            Locale::ZhTw,
+            options(),
+        )?;
+        assert_eq!(out, expected("瀏覽器支援"));
+        // Without a table following it's a regular block quote.
+        let out = m2h_internal("> **Caption:** Foo", Locale::EnUs, options())?;
+        assert_eq!(
+            out,
+            "<blockquote>\n<p><strong>Caption:</strong> Foo</p>\n</blockquote>\n"
+        );
+        // Without the option it's a regular block quote too.
+        let out = m2h_internal(
+            &format!("> **Caption:** Foo\n\n{table}"),
+            Locale::EnUs,
+            M2HOptions {
+                sourcepos: false,
+                ..Default::default()
+            },
+        )?;
+        assert!(out.starts_with(
+            "<blockquote>\n<p><strong>Caption:</strong> Foo</p>\n</blockquote>\n<table>\n<thead>"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn list_item_depth() -> Result<(), anyhow::Error> {
+        let options = M2HOptions {
+            sourcepos: false,
+            list_item_depth: true,
+            ..Default::default()
+        };
+        let out = m2h_internal("- a\n  1. b\n- c\n", Locale::EnUs, options)?;
+        assert_eq!(
+            out,
+            "<ul data-depth=\"0\">\n<li data-depth=\"0\">a\n<ol data-depth=\"1\">\n<li data-depth=\"1\">b</li>\n</ol>\n</li>\n<li data-depth=\"0\">c</li>\n</ul>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn anchor_id_rewriter() -> Result<(), anyhow::Error> {
+        let options = M2HOptions {
+            sourcepos: false,
+            anchor_id_rewriter: Some(Box::new(|id: &str| id.to_uppercase())),
+            ..Default::default()
+        };
+        let out = m2h_internal("# Foo\n\n# foo\n\n## Bar baz", Locale::EnUs, options)?;
+        assert_eq!(
+            out,
+            "<h1 id=\"FOO\">Foo</h1>\n<h1 id=\"FOO_2\">foo</h1>\n<h2 id=\"BAR_BAZ\">Bar baz</h2>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn details() -> Result<(), anyhow::Error> {
+        let options = || M2HOptions {
+            sourcepos: false,
+            ..Default::default()
+        };
+        let out = m2h_internal(
+            "```details Show <more>\nSome *emphasis*.\n\n- a\n- b\n```\n",
+            Locale::EnUs,
+            options(),
+        )?;
+        assert_eq!(
+            out,
+            "<details>\n<summary>Show &lt;more&gt;</summary>\n<p>Some <em>emphasis</em>.</p>\n<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n</details>\n"
+        );
+        let out = m2h_internal(
+            "para\n\n```details Title\nSome *emphasis*.\n```\n\n- a\n\n  ```details Nested\n  b\n  ```\n",
+            Locale::EnUs,
+            Default::default(),
+        )?;
+        assert_eq!(
+            out,
+            "<p data-sourcepos=\"1:1-1:4\">para</p>\n<details data-sourcepos=\"3:1-5:3\">\n<summary>Title</summary>\n<p data-sourcepos=\"4:1-4:16\">Some <em data-sourcepos=\"4:6-4:15\">emphasis</em>.</p>\n</details>\n<ul data-sourcepos=\"7:1-11:5\">\n<li data-sourcepos=\"7:1-11:5\">\n<p data-sourcepos=\"7:3-7:3\">a</p>\n<details data-sourcepos=\"9:3-11:5\">\n<summary>Nested</summary>\n<p data-sourcepos=\"10:3-10:3\">b</p>\n</details>\n</li>\n</ul>\n"
+        );
+        let out = m2h_internal(
+            "  ```details T\n  b\n  ```\n",
+            Locale::EnUs,
+            Default::default(),
+        )?;
+        assert!(out.contains("<p data-sourcepos=\"2:3-2:3\">b</p>"));
+        let out = m2h_internal("```detailsx\nfoo\n```\n", Locale::EnUs, options())?;
+        assert_eq!(
+            out,
+            "<pre class=\"brush: detailsx notranslate\">foo\n</pre>\n"
+        );
+        // Footnotes stay out of the details, the document's own are closed
+        // at its end.
+        let arena = Arena::new();
+        let mut comrak_options = comrak_options(&options());
+        comrak_options.extension.footnotes = true;
+        let root = parse(
+            &arena,
+            "a[^1]\n\n```details T\nb[^2]\n\n[^2]: inner\n```\n\n[^1]: outer\n",
+            &comrak_options,
+            &options(),
+        );
+        let mut html = vec![];
+        format_document(root, &comrak_options, &options(), &mut html, Locale::EnUs)?;
+        let out = String::from_utf8(html)?;
+        assert!(out.contains("<details>\n<summary>T</summary>\n<p>b[^2]</p>\n</details>\n"));
+        assert!(!out.contains("inner"));
+        assert_eq!(out.matches("<section").count(), 1);
+        assert!(out.ends_with("</ol>\n</section>\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn spoiler_attr() -> Result<(), anyhow::Error> {
+        let render = |spoiler_attr: SpoilerAttr| {
+            let arena = Arena::new();
+            let mut options = ComrakOptions::default();
+            options.extension.spoiler = true;
+            let root = parse_document(&arena, "a ||secret|| b", &options);
+            let mut html = vec![];
+            format_document(
+                root,
+                &options,
+                &M2HOptions {
+                    spoiler_attr,
+                    ..Default::default()
+                },
+                &mut html,
+                Locale::EnUs,
+            )
+            .unwrap();
+            String::from_utf8(html).unwrap()
+        };
+        assert_eq!(
+            render(SpoilerAttr::None),
+            "<p>a <span class=\"spoiler\">secret</span> b</p>\n"
+        );
+        assert_eq!(
+            render(SpoilerAttr::Focusable),
+            "<p>a <span class=\"spoiler\" tabindex=\"0\" aria-expanded=\"false\">secret</span> b</p>\n"
+        );
+        assert_eq!(
+            render(SpoilerAttr::Data),
+            "<p>a <span class=\"spoiler\" data-spoiler>secret</span> b</p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn collapsible_alert() -> Result<(), anyhow::Error> {
+        let render = |input: &str| {
+            let arena = Arena::new();
+            let mut options = ComrakOptions::default();
+            options.extension.alerts = true;
+            let root = parse_document(&arena, input, &options);
+            let mut html = vec![];
+            format_document(
+                root,
+                &options,
+                &M2HOptions {
+                    sourcepos: false,
+                    ..Default::default()
+                },
+                &mut html,
+                Locale::EnUs,
+            )
+            .unwrap();
+            String::from_utf8(html).unwrap()
+        };
+        assert_eq!(
+            render("> [!NOTE]\n> foobar"),
+            "<div class=\"markdown-alert markdown-alert-note\">\n<p class=\"markdown-alert-title\">Note</p>\n<p>foobar</p>\n</div>\n"
+        );
+        assert_eq!(
+            render("> [!NOTE]-\n> foobar"),
+            "<details class=\"markdown-alert markdown-alert-note\">\n<summary class=\"markdown-alert-title\">Note</summary>\n<p>foobar</p>\n</details>\n"
+        );
+        assert_eq!(
+            render("> [!TIP]+ Read <this>\n> foobar"),
+            "<details class=\"markdown-alert markdown-alert-tip\" open>\n<summary class=\"markdown-alert-title\">Read &lt;this&gt;</summary>\n<p>foobar</p>\n</details>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn auto_number_headings() -> Result<(), anyhow::Error> {
+        let options = M2HOptions {
+            sourcepos: false,
+            auto_number_headings: true,
+            ..Default::default()
+        };
+        let out = m2h_internal("## A\n### B\n### C\n## D", Locale::EnUs, options)?;
+        assert_eq!(
+            out,
+            concat!(
+                "<h2 id=\"a\"><span class=\"heading-number\">1</span> A</h2>\n",
+                "<h3 id=\"b\"><span class=\"heading-number\">1.1</span> B</h3>\n",
+                "<h3 id=\"c\"><span class=\"heading-number\">1.2</span> C</h3>\n",
+                "<h2 id=\"d\"><span class=\"heading-number\">2</span> D</h2>\n",
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn smart_punctuation() -> Result<(), anyhow::Error> {
+        let options = || M2HOptions {
+            sourcepos: false,
+            smart_punctuation: true,
+            ..Default::default()
+        };
+        let input = "\"Quote *\"inner\"*\" -- `\"code\"` it's\n\n\"next\"";
+        let out = m2h_internal(input, Locale::EnUs, options())?;
+        assert_eq!(
+            out,
+            "<p>“Quote <em>“inner”</em>” – <code>&quot;code&quot;</code> it’s</p>\n<p>“next”</p>\n"
+        );
+        let out = m2h_internal(input, Locale::Fr, options())?;
+        assert_eq!(
+            out,
+            "<p>«\u{202F}Quote <em>«\u{202F}inner\u{202F}»</em>\u{202F}» – <code>&quot;code&quot;</code> it’s</p>\n<p>«\u{202F}next\u{202F}»</p>\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn frontmatter_json() -> Result<(), anyhow::Error> {
+        let options = M2HOptions {
+            sourcepos: false,
+            emit_frontmatter_json: true,
+            ..Default::default()
+        };
+        let (out, diagnostics) = m2h_internal_with_diagnostics(
+            "---\ntitle: Foo </script>\nslug: Web/Foo\n---\n\nbar",
+            Locale::EnUs,
+            options,
+        )?;
+        assert_eq!(
+            out,
+            "<script type=\"application/json\" data-frontmatter>{\"title\":\"Foo <\\/script>\",\"slug\":\"Web/Foo\"}</script>\n<p>bar</p>\n"
+        );
+        assert!(diagnostics.is_empty());
+
+        let options = M2HOptions {
+            sourcepos: false,
+            emit_frontmatter_json: true,
+            ..Default::default()
+        };
+        let (out, diagnostics) =
+            m2h_internal_with_diagnostics("---\ntitle: [Foo\n---\n\nbar", Locale::EnUs, options)?;
+        assert_eq!(out, "<p>bar</p>\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].kind,
+            RenderDiagnosticKind::InvalidFrontMatter
+        );
+        Ok(())
+    }
+
     #[test]
     fn escape_hrefs() -> Result<(), anyhow::Error> {
         fn eh(s: &str) -> Result<String, anyhow::Error> {
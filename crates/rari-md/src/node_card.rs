@@ -5,70 +5,114 @@ pub enum NoteCard {
     Callout,
     Warning,
     Note,
+    /// Not rendered as a card, the block quote captions the table following it.
+    Caption,
 }
 
+/// The old-style prefixes of callouts, warnings, notes and captions, for all
+/// locales with translated note cards.
+const PREFIXES: [(Locale, [&str; 4]); 10] = [
+    (Locale::EnUs, ["Callout:", "Warning:", "Note:", "Caption:"]),
+    (
+        Locale::De,
+        ["Aufruf:", "Warnung:", "Hinweis:", "Beschriftung:"],
+    ),
+    (
+        Locale::Es,
+        ["Observación:", "Advertencia:", "Nota:", "Leyenda:"],
+    ),
+    (
+        Locale::Fr,
+        ["Remarque :", "Attention :", "Note :", "Légende :"],
+    ),
+    (Locale::Ja, ["注目:", "警告:", "メモ:", "キャプション:"]),
+    (Locale::Ko, ["알림 :", "경고 :", "참고 :", "캡션 :"]),
+    (Locale::PtBr, ["Observação:", "Aviso:", "Nota:", "Legenda:"]),
+    (
+        Locale::Ru,
+        ["Сноска:", "Предупреждение:", "Примечание:", "Подпись:"],
+    ),
+    (Locale::ZhCn, ["标注：", "警告：", "备注：", "说明："]),
+    (Locale::ZhTw, ["標註：", "警告：", "備註：", "說明："]),
+];
+
 impl NoteCard {
+    /// The locales with translated note card prefixes and titles.
+    pub fn supported_locales() -> impl Iterator<Item = Locale> {
+        PREFIXES.into_iter().map(|(locale, _)| locale)
+    }
+
+    /// All note cards with their old-style prefix in `locale`.
+    pub fn prefixes_for_locale(locale: Locale) -> [(NoteCard, &'static str); 4] {
+        [
+            (
+                NoteCard::Callout,
+                NoteCard::Callout.prefix_for_locale(locale),
+            ),
+            (
+                NoteCard::Warning,
+                NoteCard::Warning.prefix_for_locale(locale),
+            ),
+            (NoteCard::Note, NoteCard::Note.prefix_for_locale(locale)),
+            (
+                NoteCard::Caption,
+                NoteCard::Caption.prefix_for_locale(locale),
+            ),
+        ]
+    }
+
+    /// The old-style prefix in `locale`, the English one for locales without
+    /// translated note cards.
     pub fn prefix_for_locale(&self, locale: Locale) -> &str {
-        match (self, locale) {
-            (Self::Callout, Locale::De) => "Aufruf:",
-            (Self::Warning, Locale::De) => "Warnung:",
-            (Self::Note, Locale::De) => "Hinweis:",
-            (Self::Callout, Locale::EnUs) => "Callout:",
-            (Self::Warning, Locale::EnUs) => "Warning:",
-            (Self::Note, Locale::EnUs) => "Note:",
-            (Self::Callout, Locale::Es) => "Observación:",
-            (Self::Warning, Locale::Es) => "Advertencia:",
-            (Self::Note, Locale::Es) => "Nota:",
-            (Self::Callout, Locale::Fr) => "Remarque :",
-            (Self::Warning, Locale::Fr) => "Attention :",
-            (Self::Note, Locale::Fr) => "Note :",
-            (Self::Callout, Locale::Ja) => "注目:",
-            (Self::Warning, Locale::Ja) => "警告:",
-            (Self::Note, Locale::Ja) => "メモ:",
-            (Self::Callout, Locale::Ko) => "알림 :",
-            (Self::Warning, Locale::Ko) => "경고 :",
-            (Self::Note, Locale::Ko) => "참고 :",
-            (Self::Callout, Locale::PtBr) => "Observação:",
-            (Self::Warning, Locale::PtBr) => "Aviso:",
-            (Self::Note, Locale::PtBr) => "Nota:",
-            (Self::Callout, Locale::Ru) => "Сноска:",
-            (Self::Warning, Locale::Ru) => "Предупреждение:",
-            (Self::Note, Locale::Ru) => "Примечание:",
-            (Self::Callout, Locale::ZhCn) => "标注：",
-            (Self::Warning, Locale::ZhCn) => "警告：",
-            (Self::Note, Locale::ZhCn) => "备注：",
-            (Self::Callout, Locale::ZhTw) => "標註：",
-            (Self::Warning, Locale::ZhTw) => "警告：",
-            (Self::Note, Locale::ZhTw) => "備註：",
+        let (_, prefixes) = PREFIXES
+            .iter()
+            .find(|(supported, _)| *supported == locale)
+            .unwrap_or(&PREFIXES[0]);
+        match self {
+            Self::Callout => prefixes[0],
+            Self::Warning => prefixes[1],
+            Self::Note => prefixes[2],
+            Self::Caption => prefixes[3],
         }
     }
+
+    /// Returns the localized title, which is the prefix without its trailing colon.
+    pub fn title_for_locale(&self, locale: Locale) -> &str {
+        self.prefix_for_locale(locale)
+            .trim_end_matches([':', '：'])
+            .trim_end()
+    }
+
     pub fn new_prefix(&self) -> &str {
         match self {
             Self::Callout => "[!CALLOUT]",
             Self::Warning => "[!WARNING]",
             Self::Note => "[!NOTE]",
+            Self::Caption => "[!CAPTION]",
         }
     }
 }
 
-pub(crate) fn is_callout<'a>(block_quote: &'a AstNode<'a>, locale: Locale) -> Option<NoteCard> {
+/// Detects a note card and removes its marker.
+///
+/// With `keep_marker`, the bold marker of old-style cards (`**Note:**`) stays
+/// in the body. The marker of new-style cards (`[!NOTE]`) is always removed.
+pub(crate) fn is_callout<'a>(
+    block_quote: &'a AstNode<'a>,
+    locale: Locale,
+    keep_marker: bool,
+) -> Option<(NoteCard, CardAttributes)> {
     if let Some(grand_child) = block_quote.first_child().and_then(|c| c.first_child()) {
         if matches!(grand_child.data.borrow().value, NodeValue::Strong) {
             if let Some(marker) = grand_child.first_child() {
                 if let NodeValue::Text(ref text) = marker.data.borrow().value {
-                    let callout = NoteCard::Callout.prefix_for_locale(locale);
-                    if text.starts_with(callout) {
-                        grand_child.detach();
-                        return Some(NoteCard::Callout);
-                    }
-
-                    if text.starts_with(NoteCard::Warning.prefix_for_locale(locale)) {
-                        grand_child.detach();
-                        return Some(NoteCard::Warning);
-                    }
-                    if text.starts_with(NoteCard::Note.prefix_for_locale(locale)) {
-                        grand_child.detach();
-                        return Some(NoteCard::Note);
+                    for card in [NoteCard::Callout, NoteCard::Warning, NoteCard::Note] {
+                        if starts_with_prefix(text, card.prefix_for_locale(locale)) {
+                            if !keep_marker {
+                                grand_child.detach();
+                            }
+                            return Some((card, CardAttributes::default()));
+                        }
                     }
                 }
             }
@@ -78,29 +122,17 @@ pub(crate) fn is_callout<'a>(block_quote: &'a AstNode<'a>, locale: Locale) -> Op
         if let Some(marker) = child.first_child() {
             let mut data = marker.data.borrow_mut();
             if let NodeValue::Text(ref text) = data.value {
-                if text.starts_with(NoteCard::Callout.new_prefix()) {
-                    if text.trim() == NoteCard::Callout.new_prefix() {
-                        marker.detach();
-                    } else if let Some(tail) = text.strip_prefix(NoteCard::Callout.new_prefix()) {
-                        data.value = NodeValue::Text(tail.trim().to_string());
-                    }
-                    return Some(NoteCard::Callout);
-                }
-                if text.starts_with(NoteCard::Warning.new_prefix()) {
-                    if text.trim() == NoteCard::Warning.new_prefix() {
-                        marker.detach();
-                    } else if let Some(tail) = text.strip_prefix(NoteCard::Warning.new_prefix()) {
-                        data.value = NodeValue::Text(tail.trim().to_string());
-                    }
-                    return Some(NoteCard::Warning);
-                }
-                if text.starts_with(NoteCard::Note.new_prefix()) {
-                    if text.trim() == NoteCard::Note.new_prefix() {
-                        marker.detach();
-                    } else if let Some(tail) = text.strip_prefix(NoteCard::Note.new_prefix()) {
-                        data.value = NodeValue::Text(tail.trim().to_string());
+                for card in [NoteCard::Callout, NoteCard::Warning, NoteCard::Note] {
+                    if let Some(tail) = text.strip_prefix(card.new_prefix()) {
+                        let (attributes, tail) = split_card_attributes(tail);
+                        let tail = tail.trim().to_string();
+                        if tail.is_empty() {
+                            marker.detach();
+                        } else {
+                            data.value = NodeValue::Text(tail);
+                        }
+                        return Some((card, attributes));
                     }
-                    return Some(NoteCard::Note);
                 }
             }
         }
@@ -108,15 +140,197 @@ pub(crate) fn is_callout<'a>(block_quote: &'a AstNode<'a>, locale: Locale) -> Op
     None
 }
 
-/// Returns the default title for an alert type
-pub fn alert_type_default_title(alert_type: &AlertType) -> String {
-    match *alert_type {
-        AlertType::Note => String::from("Note"),
-        AlertType::Tip => String::from("Tip"),
-        AlertType::Important => String::from("Important"),
-        AlertType::Warning => String::from("Warning"),
-        AlertType::Caution => String::from("Caution"),
+/// Extra attributes of a note card, given as `{.class #id}` after the marker.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CardAttributes {
+    pub id: Option<String>,
+    pub classes: Vec<String>,
+}
+
+/// Splits a leading `{.class #id}` block off `tail`. If there is none, or it
+/// is not valid, the attributes are empty and `tail` is returned unchanged.
+fn split_card_attributes(tail: &str) -> (CardAttributes, &str) {
+    let Some((block, rest)) = tail
+        .trim_start()
+        .strip_prefix('{')
+        .and_then(|s| s.split_once('}'))
+    else {
+        return (CardAttributes::default(), tail);
+    };
+    let is_name = |name: &str| {
+        !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+    };
+    let mut attributes = CardAttributes::default();
+    for token in block.split_whitespace() {
+        if let Some(class) = token.strip_prefix('.').filter(|class| is_name(class)) {
+            attributes.classes.push(class.to_string());
+        } else if let Some(id) = token.strip_prefix('#').filter(|id| is_name(id)) {
+            attributes.id = Some(id.to_string());
+        } else {
+            return (CardAttributes::default(), tail);
+        }
     }
+    (attributes, rest)
+}
+
+/// Returns `true` if `text` starts with the old-style `prefix`, ignoring the
+/// spacing in front of the colon. Locales differ in whether they put a space
+/// before the colon (`Remarque :`, `알림 :`), and authors do not always follow.
+fn starts_with_prefix(text: &str, prefix: &str) -> bool {
+    fn without_colon_spacing(s: &str) -> String {
+        let mut out = String::with_capacity(s.len());
+        for c in s.chars() {
+            if matches!(c, ':' | '：') {
+                out.truncate(out.trim_end().len());
+            }
+            out.push(c);
+        }
+        out
+    }
+    without_colon_spacing(text).starts_with(&without_colon_spacing(prefix))
+}
+
+/// Removes the caption prefix from the block quote and returns the paragraph
+/// holding the caption.
+pub(crate) fn take_caption<'a>(
+    block_quote: &'a AstNode<'a>,
+    locale: Locale,
+) -> Option<&'a AstNode<'a>> {
+    let (paragraph, marker) = caption_marker(block_quote, locale)?;
+    let tail = match marker.data.borrow().value {
+        NodeValue::Text(ref text) => text
+            .strip_prefix(NoteCard::Caption.new_prefix())
+            .map(|tail| tail.to_string()),
+        _ => None,
+    };
+    match tail {
+        Some(tail) if !tail.trim().is_empty() => {
+            marker.data.borrow_mut().value = NodeValue::Text(tail.trim().to_string())
+        }
+        _ => marker.detach(),
+    }
+    // Drop the whitespace between the prefix and the caption.
+    if let Some(first) = paragraph.first_child() {
+        let mut data = first.data.borrow_mut();
+        match data.value {
+            NodeValue::SoftBreak | NodeValue::LineBreak => {
+                drop(data);
+                first.detach();
+            }
+            NodeValue::Text(ref mut text) => *text = text.trim_start().to_string(),
+            _ => {}
+        }
+    }
+    Some(paragraph)
+}
+
+/// Removes a trailing `-- https://source` line from the block quote and
+/// returns the cited URL. The block quote is left unchanged if nothing else
+/// would remain in it.
+pub(crate) fn take_citation<'a>(block_quote: &'a AstNode<'a>) -> Option<String> {
+    let paragraph = block_quote.last_child()?;
+    if !matches!(paragraph.data.borrow().value, NodeValue::Paragraph) {
+        return None;
+    }
+    let mut line = vec![];
+    let mut line_break = paragraph.last_child();
+    while let Some(node) = line_break {
+        if matches!(
+            node.data.borrow().value,
+            NodeValue::SoftBreak | NodeValue::LineBreak
+        ) {
+            break;
+        }
+        line.push(node);
+        line_break = node.previous_sibling();
+    }
+    // The URL may have been autolinked, so use the link target.
+    let mut text = String::new();
+    for node in line.iter().rev() {
+        match node.data.borrow().value {
+            NodeValue::Text(ref literal) => text.push_str(literal),
+            NodeValue::Link(ref link) => text.push_str(&link.url),
+            _ => return None,
+        }
+    }
+    let url = text.trim().strip_prefix("--")?.trim();
+    if !(url.starts_with("https://") || url.starts_with("http://"))
+        || url.contains(char::is_whitespace)
+    {
+        return None;
+    }
+    if line_break.is_none() && paragraph.previous_sibling().is_none() {
+        return None;
+    }
+    let url = url.to_string();
+    for node in line {
+        node.detach();
+    }
+    match line_break {
+        Some(line_break) => line_break.detach(),
+        None => paragraph.detach(),
+    }
+    Some(url)
+}
+
+fn caption_marker<'a>(
+    block_quote: &'a AstNode<'a>,
+    locale: Locale,
+) -> Option<(&'a AstNode<'a>, &'a AstNode<'a>)> {
+    let paragraph = block_quote.first_child()?;
+    let marker = paragraph.first_child()?;
+    let is_marker = match marker.data.borrow().value {
+        NodeValue::Strong => marker.first_child().is_some_and(|text| {
+            matches!(text.data.borrow().value, NodeValue::Text(ref text)
+                if starts_with_prefix(text, NoteCard::Caption.prefix_for_locale(locale)))
+        }),
+        NodeValue::Text(ref text) => text.starts_with(NoteCard::Caption.new_prefix()),
+        _ => false,
+    };
+    is_marker.then_some((paragraph, marker))
+}
+
+/// Returns the default title for an alert type in `locale`. Notes and warnings
+/// share their titles with the corresponding `NoteCard`.
+pub fn alert_type_default_title(alert_type: &AlertType, locale: Locale) -> String {
+    let title = match (alert_type, locale) {
+        (AlertType::Note, _) => NoteCard::Note.title_for_locale(locale),
+        (AlertType::Warning, _) => NoteCard::Warning.title_for_locale(locale),
+        (AlertType::Tip, Locale::EnUs) => "Tip",
+        (AlertType::Tip, Locale::De) => "Tipp",
+        (AlertType::Tip, Locale::Es) => "Consejo",
+        (AlertType::Tip, Locale::Fr) => "Astuce",
+        (AlertType::Tip, Locale::Ja) => "ヒント",
+        (AlertType::Tip, Locale::Ko) => "팁",
+        (AlertType::Tip, Locale::PtBr) => "Dica",
+        (AlertType::Tip, Locale::Ru) => "Совет",
+        (AlertType::Tip, Locale::ZhCn) => "提示",
+        (AlertType::Tip, Locale::ZhTw) => "提示",
+        (AlertType::Important, Locale::EnUs) => "Important",
+        (AlertType::Important, Locale::De) => "Wichtig",
+        (AlertType::Important, Locale::Es) => "Importante",
+        (AlertType::Important, Locale::Fr) => "Important",
+        (AlertType::Important, Locale::Ja) => "重要",
+        (AlertType::Important, Locale::Ko) => "중요",
+        (AlertType::Important, Locale::PtBr) => "Importante",
+        (AlertType::Important, Locale::Ru) => "Важно",
+        (AlertType::Important, Locale::ZhCn) => "重要",
+        (AlertType::Important, Locale::ZhTw) => "重要",
+        (AlertType::Caution, Locale::EnUs) => "Caution",
+        (AlertType::Caution, Locale::De) => "Vorsicht",
+        (AlertType::Caution, Locale::Es) => "Precaución",
+        (AlertType::Caution, Locale::Fr) => "Prudence",
+        (AlertType::Caution, Locale::Ja) => "注意",
+        (AlertType::Caution, Locale::Ko) => "주의",
+        (AlertType::Caution, Locale::PtBr) => "Cuidado",
+        (AlertType::Caution, Locale::Ru) => "Осторожно",
+        (AlertType::Caution, Locale::ZhCn) => "注意",
+        (AlertType::Caution, Locale::ZhTw) => "注意",
+    };
+    title.to_string()
 }
 
 /// Returns the CSS class to use for an alert type
@@ -129,3 +343,77 @@ pub fn alert_type_css_class(alert_type: &AlertType) -> String {
         AlertType::Caution => String::from("markdown-alert-caution"),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_starts_with_prefix() {
+        assert!(starts_with_prefix("Note:", "Note:"));
+        assert!(starts_with_prefix("Note :", "Note:"));
+        assert!(starts_with_prefix("Note: ", "Note:"));
+        assert!(starts_with_prefix("Remarque:", "Remarque :"));
+        assert!(starts_with_prefix("Remarque\u{a0}:", "Remarque :"));
+        assert!(starts_with_prefix("참고:", "참고 :"));
+        assert!(!starts_with_prefix("Notes:", "Note:"));
+    }
+
+    #[test]
+    fn test_supported_locales() {
+        let locales = NoteCard::supported_locales().collect::<Vec<_>>();
+        assert_eq!(locales.first(), Some(&Locale::EnUs));
+        assert_eq!(
+            locales.iter().collect::<HashSet<_>>().len(),
+            locales.len(),
+            "a locale is listed twice"
+        );
+        for locale in locales {
+            for (card, prefix) in NoteCard::prefixes_for_locale(locale) {
+                assert!(!prefix.is_empty());
+                assert!(!card.title_for_locale(locale).is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_card_attributes() {
+        let (attributes, rest) = split_card_attributes(" {.compact #tips .wide} foo");
+        assert_eq!(
+            attributes,
+            CardAttributes {
+                id: Some("tips".to_string()),
+                classes: vec!["compact".to_string(), "wide".to_string()],
+            }
+        );
+        assert_eq!(rest, " foo");
+        let (attributes, rest) = split_card_attributes(" {.compact foo=bar}");
+        assert_eq!(attributes, CardAttributes::default());
+        assert_eq!(rest, " {.compact foo=bar}");
+        let (attributes, rest) = split_card_attributes(" {.compact");
+        assert_eq!(attributes, CardAttributes::default());
+        assert_eq!(rest, " {.compact");
+    }
+
+    #[test]
+    fn test_alert_type_default_title() {
+        assert_eq!(
+            alert_type_default_title(&AlertType::Warning, Locale::EnUs),
+            "Warning"
+        );
+        assert_eq!(
+            alert_type_default_title(&AlertType::Warning, Locale::Fr),
+            "Attention"
+        );
+        assert_eq!(
+            alert_type_default_title(&AlertType::Note, Locale::ZhCn),
+            "备注"
+        );
+        assert_eq!(
+            alert_type_default_title(&AlertType::Tip, Locale::Fr),
+            "Astuce"
+        );
+    }
+}
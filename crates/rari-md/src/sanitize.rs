@@ -0,0 +1,369 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+
+use html5ever::tendril::StrTendril;
+use html5ever::tokenizer::states::{RawKind, Rawtext, Rcdata, ScriptData};
+use html5ever::tokenizer::{
+    BufferQueue, Tag, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts,
+};
+
+/// Raw HTML allowed by [`M2HOptions::sanitize`](crate::M2HOptions::sanitize).
+///
+/// Tags not in `tags` are dropped, keeping their content, except for the
+/// content of `<script>`, `<style>` and the like, which is dropped as well.
+/// Attributes not in `attributes` are dropped. Event handlers (`on…`) and
+/// URLs with a scheme other than `http(s)`, `mailto` and `tel` are always
+/// dropped, as are comments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizeConfig {
+    /// Allowed tag names, lowercase.
+    pub tags: HashSet<String>,
+    /// Allowed attribute names on any allowed tag, lowercase.
+    pub attributes: HashSet<String>,
+}
+
+impl Default for SanitizeConfig {
+    fn default() -> Self {
+        let tags = [
+            "a",
+            "abbr",
+            "b",
+            "bdi",
+            "bdo",
+            "blockquote",
+            "br",
+            "caption",
+            "cite",
+            "code",
+            "dd",
+            "del",
+            "details",
+            "dfn",
+            "div",
+            "dl",
+            "dt",
+            "em",
+            "figcaption",
+            "figure",
+            "h1",
+            "h2",
+            "h3",
+            "h4",
+            "h5",
+            "h6",
+            "hr",
+            "i",
+            "img",
+            "ins",
+            "kbd",
+            "li",
+            "mark",
+            "ol",
+            "p",
+            "pre",
+            "q",
+            "s",
+            "samp",
+            "small",
+            "span",
+            "strong",
+            "sub",
+            "summary",
+            "sup",
+            "table",
+            "tbody",
+            "td",
+            "tfoot",
+            "th",
+            "thead",
+            "time",
+            "tr",
+            "u",
+            "ul",
+            "var",
+            "wbr",
+        ];
+        let attributes = [
+            "alt", "class", "colspan", "datetime", "dir", "height", "href", "id", "lang", "open",
+            "rowspan", "src", "start", "title", "width",
+        ];
+        SanitizeConfig {
+            tags: tags.into_iter().map(String::from).collect(),
+            attributes: attributes.into_iter().map(String::from).collect(),
+        }
+    }
+}
+
+/// Elements whose content is not markup, it is dropped along with them.
+const RAW_TEXT_TAGS: [(&str, RawKind); 9] = [
+    ("script", ScriptData),
+    ("style", Rawtext),
+    ("iframe", Rawtext),
+    ("noembed", Rawtext),
+    ("noframes", Rawtext),
+    ("noscript", Rawtext),
+    ("xmp", Rawtext),
+    ("textarea", Rcdata),
+    ("title", Rcdata),
+];
+
+/// Attributes holding a URL.
+const URL_ATTRIBUTES: [&str; 8] = [
+    "action",
+    "background",
+    "cite",
+    "formaction",
+    "href",
+    "poster",
+    "src",
+    "xlink:href",
+];
+
+/// Returns `html` with everything removed that `config` does not allow.
+///
+/// `html` is tokenized by html5ever the way a browser would, so comments
+/// closed early (`<!-->`), CDATA sections and malformed tags end where they
+/// end in a browser. Comments, doctypes and processing instructions are
+/// dropped. Each fragment is sanitized on its own, unbalanced tags are kept
+/// as they are, since raw HTML in markdown is split around the markdown in
+/// between.
+pub(crate) fn sanitize_html(html: &str, config: &SanitizeConfig) -> String {
+    let sink = Sanitizer {
+        config,
+        out: RefCell::new(String::with_capacity(html.len())),
+        skip: Cell::new(false),
+    };
+    let input = BufferQueue::default();
+    input.push_back(StrTendril::from_slice(html));
+    let tokenizer = Tokenizer::new(sink, TokenizerOpts::default());
+    let _ = tokenizer.feed(&input);
+    tokenizer.end();
+    tokenizer.sink.out.into_inner()
+}
+
+struct Sanitizer<'a> {
+    config: &'a SanitizeConfig,
+    out: RefCell<String>,
+    /// Inside a raw text element which is dropped.
+    skip: Cell<bool>,
+}
+
+impl TokenSink for Sanitizer<'_> {
+    type Handle = ();
+
+    fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+        match token {
+            Token::CharacterTokens(text) if !self.skip.get() => {
+                escape_text(&mut self.out.borrow_mut(), &text)
+            }
+            Token::TagToken(tag) => {
+                let name: &str = &tag.name;
+                let allowed = self.config.tags.contains(name);
+                if tag.kind == TagKind::EndTag {
+                    self.skip.set(false);
+                    if allowed {
+                        let mut out = self.out.borrow_mut();
+                        out.push_str("</");
+                        out.push_str(name);
+                        out.push('>');
+                    }
+                    return TokenSinkResult::Continue;
+                }
+                if allowed {
+                    write_tag(&mut self.out.borrow_mut(), &tag, self.config);
+                }
+                if tag.self_closing {
+                    return TokenSinkResult::Continue;
+                }
+                if name == "plaintext" {
+                    self.skip.set(!allowed);
+                    return TokenSinkResult::Plaintext;
+                }
+                if let Some((_, kind)) = RAW_TEXT_TAGS.iter().find(|(raw, _)| *raw == name) {
+                    self.skip.set(!allowed);
+                    return TokenSinkResult::RawData(*kind);
+                }
+            }
+            _ => {}
+        }
+        TokenSinkResult::Continue
+    }
+}
+
+fn escape_text(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            c => out.push(c),
+        }
+    }
+}
+
+fn write_tag(out: &mut String, tag: &Tag, config: &SanitizeConfig) {
+    out.push('<');
+    out.push_str(&tag.name);
+    for attr in &tag.attrs {
+        let name: &str = &attr.name.local;
+        if name.starts_with("on") || !config.attributes.contains(name) {
+            continue;
+        }
+        if URL_ATTRIBUTES.contains(&name) && !is_safe_url(&attr.value) {
+            continue;
+        }
+        out.push(' ');
+        out.push_str(name);
+        out.push_str("=\"");
+        for c in attr.value.chars() {
+            match c {
+                '&' => out.push_str("&amp;"),
+                '"' => out.push_str("&quot;"),
+                c => out.push(c),
+            }
+        }
+        out.push('"');
+    }
+    if tag.self_closing {
+        out.push_str(" /");
+    }
+    out.push('>');
+}
+
+/// Whether `url` is relative or has a scheme that cannot run script.
+///
+/// `url` is the decoded attribute value. Browsers ignore leading and trailing
+/// whitespace and control characters, and tabs and newlines anywhere.
+fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .trim_matches(|c: char| c <= ' ')
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    let scheme_end = url.find(['/', '?', '#']).unwrap_or(url.len());
+    match url[..scheme_end].split_once(':') {
+        Some((scheme, _)) => ["http", "https", "mailto", "tel"]
+            .iter()
+            .any(|safe| scheme.eq_ignore_ascii_case(safe)),
+        None => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_html() {
+        let config = SanitizeConfig::default();
+        assert_eq!(
+            sanitize_html(
+                r#"<kbd onclick="alert(1)" class='key "x"'>Ctrl</kbd>"#,
+                &config
+            ),
+            r#"<kbd class="key &quot;x&quot;">Ctrl</kbd>"#
+        );
+        assert_eq!(
+            sanitize_html(
+                "<p>a<script>alert('</p>')</script>b<SCRIPT src=x></SCRIPT></p>",
+                &config
+            ),
+            "<p>ab</p>"
+        );
+        assert_eq!(
+            sanitize_html(
+                r#"<a href="javascript:alert(1)">x</a><a href="&#106;avascript:alert(1)">y</a><a href="/en-US/docs/Web" title=t>z</a>"#,
+                &config
+            ),
+            r#"<a>x</a><a>y</a><a href="/en-US/docs/Web" title="t">z</a>"#
+        );
+        assert_eq!(
+            sanitize_html("<!-- ks____1 --><custom-el>text</custom-el> 1 < 2", &config),
+            "text 1 &lt; 2"
+        );
+        assert_eq!(
+            sanitize_html("<br/><img src=a.png onerror=x>", &config),
+            "<br /><img src=\"a.png\">"
+        );
+        assert_eq!(
+            sanitize_html("<details><summary>Show", &config),
+            "<details><summary>Show"
+        );
+        assert_eq!(
+            sanitize_html("</summary></details>", &config),
+            "</summary></details>"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_html_comments() {
+        let config = SanitizeConfig::default();
+        for (input, expected) in [
+            ("<!--><script>alert(1)</script>-->", "--&gt;"),
+            ("<!---><script>alert(1)</script>-->", "--&gt;"),
+            ("<!-- <script>alert(1)</script> -->", ""),
+            ("<!-- a --!><script>alert(1)</script>", ""),
+            (
+                "<!--<!--><img src=x onerror=alert(1)>-->",
+                "<img src=\"x\">--&gt;",
+            ),
+            ("<!doctype html><?xml x?><b>b</b>", "<b>b</b>"),
+        ] {
+            assert_eq!(sanitize_html(input, &config), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_sanitize_html_cdata() {
+        let config = SanitizeConfig::default();
+        for (input, expected) in [
+            ("<![CDATA[<script>alert(1)</script>]]>", "alert(1)]]&gt;"),
+            (
+                "<![CDATA[><img src=x onerror=alert(1)>]]>",
+                "<img src=\"x\">]]&gt;",
+            ),
+        ] {
+            assert_eq!(sanitize_html(input, &config), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_sanitize_html_malformed_tags() {
+        let config = SanitizeConfig::default();
+        for (input, expected) in [
+            ("<svg/onload=alert(1)>", ""),
+            ("<img src=x onerror=alert(1)//>", "<img src=\"x\">"),
+            (
+                "<img/src=x/onerror=alert(1)>",
+                "<img src=\"x/onerror=alert(1)\">",
+            ),
+            ("<scr<script>ipt>alert(1)</script>", "ipt&gt;alert(1)"),
+            ("<<script>alert(1)//<</script>", "&lt;"),
+            ("<script>alert(1)", ""),
+            (
+                "<b onclick=alert(1) title=\"a\"b\">x</b>",
+                "<b title=\"a\">x</b>",
+            ),
+            ("<a href=\" java\tscript:alert(1)\">x</a>", "<a>x</a>"),
+            ("<a href=\"java&#x09;script:alert(1)\">x</a>", "<a>x</a>"),
+            ("<a href=\"javascript&colon;alert(1)\">x</a>", "<a>x</a>"),
+            ("<a href=\"\x01javascript:alert(1)\">x</a>", "<a>x</a>"),
+            (
+                "<img src=\"data:image/svg+xml,<svg onload=alert(1)>\">",
+                "<img>",
+            ),
+            (
+                "<style>*{}</style><textarea></textarea><b>ok</b>",
+                "<b>ok</b>",
+            ),
+            ("<plaintext><b>x</b>", ""),
+            ("<title><b onclick=x>", ""),
+            (
+                "<a title=\"&quot;><script>\">x</a>",
+                "<a title=\"&quot;><script>\">x</a>",
+            ),
+        ] {
+            assert_eq!(sanitize_html(input, &config), expected, "{input}");
+        }
+    }
+}
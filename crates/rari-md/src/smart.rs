@@ -0,0 +1,90 @@
+use rari_types::locale::Locale;
+
+/// Opening and closing quote glyphs of a locale.
+struct Quotes {
+    double: (&'static str, &'static str),
+    single: (&'static str, &'static str),
+}
+
+fn quotes_for_locale(locale: Locale) -> Quotes {
+    match locale {
+        Locale::De => Quotes {
+            double: ("„", "“"),
+            single: ("‚", "‘"),
+        },
+        Locale::Fr => Quotes {
+            double: ("«\u{202F}", "\u{202F}»"),
+            single: ("‹\u{202F}", "\u{202F}›"),
+        },
+        Locale::Es => Quotes {
+            double: ("«", "»"),
+            single: ("“", "”"),
+        },
+        Locale::Ru => Quotes {
+            double: ("«", "»"),
+            single: ("„", "“"),
+        },
+        Locale::EnUs | Locale::Ja | Locale::Ko | Locale::PtBr | Locale::ZhCn | Locale::ZhTw => {
+            Quotes {
+                double: ("“", "”"),
+                single: ("‘", "’"),
+            }
+        }
+    }
+}
+
+/// Replaces straight quotes with the typographic quotes of `locale`, and
+/// `---`/`--` with em and en dashes.
+///
+/// `prev` is the character in front of `text`, it decides whether a quote at
+/// the very start opens or closes. Apostrophes (`don't`) always become `’`.
+pub(crate) fn smarten(text: &str, prev: Option<char>, locale: Locale) -> String {
+    let quotes = quotes_for_locale(locale);
+    let mut out = String::with_capacity(text.len() + 8);
+    let mut prev = prev;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let opens = prev.map_or(true, |p| p.is_whitespace() || "([{-–—“‘„‚«‹".contains(p));
+        match c {
+            '"' if opens => out.push_str(quotes.double.0),
+            '"' => out.push_str(quotes.double.1),
+            '\'' if opens => out.push_str(quotes.single.0),
+            '\'' if chars.peek().is_some_and(|next| next.is_alphanumeric()) => out.push('’'),
+            '\'' => out.push_str(quotes.single.1),
+            '-' if chars.peek() == Some(&'-') => {
+                chars.next();
+                if chars.peek() == Some(&'-') {
+                    chars.next();
+                    out.push('—');
+                } else {
+                    out.push('–');
+                }
+            }
+            _ => out.push(c),
+        }
+        prev = out.chars().next_back();
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_smarten() {
+        assert_eq!(
+            smarten(r#"She said "don't" -- twice---"#, None, Locale::EnUs),
+            "She said “don’t” – twice—"
+        );
+        assert_eq!(
+            smarten(r#""word" it's"#, Some('x'), Locale::EnUs),
+            "”word” it’s"
+        );
+        assert_eq!(smarten(r#"'a'"#, None, Locale::De), "‚a‘");
+        assert_eq!(
+            smarten(r#"Il dit "l'homme""#, None, Locale::Fr),
+            "Il dit «\u{202F}l’homme\u{202F}»"
+        );
+    }
+}
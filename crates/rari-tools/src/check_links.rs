@@ -0,0 +1,142 @@
+use std::str::FromStr;
+
+use rari_doc::pages::page::{Page, PageLike};
+use rari_doc::pages::types::doc::Doc;
+use rari_doc::reader::read_docs_parallel;
+use rari_doc::utils::root_for_locale;
+use rari_md::{document_links, LinkKind};
+use rari_types::locale::Locale;
+
+use crate::error::ToolError;
+use crate::redirects::resolve_redirect;
+
+/// An internal link that leads nowhere.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrokenLink {
+    /// The slug of the doc containing the link.
+    pub slug: String,
+    pub url: String,
+    /// The line of the link in the markdown body of the doc.
+    pub line: usize,
+}
+
+/// The result of [`check_links`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinkReport {
+    /// The number of internal links checked.
+    pub checked: usize,
+    /// Sorted by slug and line.
+    pub broken: Vec<BrokenLink>,
+}
+
+/// Checks the internal links (`/en-US/docs/…`) of all docs in `locale`. A
+/// link is broken if it neither leads to an existing page nor is redirected
+/// to one. Translated pages falling back to `en-US` count as existing.
+///
+/// Links are taken from the markdown of the docs, links generated by macros
+/// are not checked.
+pub fn check_links(locale: Locale) -> Result<LinkReport, ToolError> {
+    let locale_root = root_for_locale(locale)?.join(locale.as_folder_str());
+    let docs = read_docs_parallel::<Page, Doc>(&[&locale_root], None)?;
+
+    let mut report = LinkReport::default();
+    for doc in &docs {
+        for link in document_links(doc.content()) {
+            if !matches!(link.kind, LinkKind::Link | LinkKind::Autolink) || !is_internal(&link.url)
+            {
+                continue;
+            }
+            report.checked += 1;
+            if !resolves(&link.url, locale) {
+                report.broken.push(BrokenLink {
+                    slug: doc.slug().to_string(),
+                    url: link.url,
+                    line: link.sourcepos.start.line,
+                });
+            }
+        }
+    }
+    report
+        .broken
+        .sort_by(|a, b| (&a.slug, a.line).cmp(&(&b.slug, b.line)));
+    Ok(report)
+}
+
+fn is_internal(url: &str) -> bool {
+    url.starts_with('/') && !url.starts_with("//")
+}
+
+/// Whether `url` leads to a page, directly or through redirects.
+fn resolves(url: &str, locale: Locale) -> bool {
+    let page_exists = |url: &str| {
+        let bare = url.split(['#', '?']).next().unwrap_or_default();
+        Page::exists_with_fallback(bare)
+    };
+    if page_exists(url) {
+        return true;
+    }
+    // Redirects live with the locale of the link, which may not be the
+    // locale of the doc.
+    let link_locale = url
+        .split('/')
+        .nth(1)
+        .and_then(|locale| Locale::from_str(locale).ok())
+        .unwrap_or(locale);
+    match resolve_redirect(url, link_locale) {
+        Some(target) => !is_internal(&target) || page_exists(&target),
+        None => false,
+    }
+}
+
+// These tests use file system fixtures to simulate content and translated content.
+// The file system is a shared resource, so we force tests to be run serially,
+// to avoid concurrent fixture management issues.
+// Using `file_serial` as a synchronization lock, we should be able to run all tests
+// using the same `key` (here: file_fixtures) to be serialized across modules.
+#[cfg(test)]
+use serial_test::file_serial;
+#[cfg(test)]
+#[file_serial(file_fixtures)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::tests::fixtures::docs::DocFixtures;
+    use crate::tests::fixtures::redirects::RedirectFixtures;
+
+    #[test]
+    fn test_check_links() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleTwo".to_string(),
+        ];
+        let redirects = vec![(
+            "docs/Web/API/OldExample".to_string(),
+            "docs/Web/API/ExampleTwo".to_string(),
+        )];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let path = root_for_locale(Locale::EnUs)
+            .unwrap()
+            .join("en-us/web/api/exampleone/index.md");
+        fs::write(
+            &path,
+            "---\ntitle: ExampleOne\nslug: Web/API/ExampleOne\n---\n\n\
+            See [two](/en-US/docs/Web/API/ExampleTwo#syntax), [old](/en-US/docs/Web/API/OldExample)\n\
+            and [example](https://example.com).\n\n\
+            [Missing](/en-US/docs/Web/API/Missing) is broken.\n",
+        )
+        .unwrap();
+
+        let report = check_links(Locale::EnUs).unwrap();
+        assert_eq!(report.checked, 3);
+        assert_eq!(
+            report.broken,
+            vec![BrokenLink {
+                slug: "Web/API/ExampleOne".to_string(),
+                url: "/en-US/docs/Web/API/Missing".to_string(),
+                line: 5,
+            }]
+        );
+    }
+}
@@ -0,0 +1,39 @@
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::Confirm;
+
+/// Asks the user whether to go ahead with a change.
+pub trait Confirmer {
+    fn confirm(&self, prompt: &str) -> bool;
+}
+
+/// Prompts on the terminal, defaulting to yes. Answers no if there is no
+/// terminal to ask on.
+pub struct TerminalConfirmer;
+
+impl Confirmer for TerminalConfirmer {
+    fn confirm(&self, prompt: &str) -> bool {
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(true)
+            .interact()
+            .unwrap_or_default()
+    }
+}
+
+/// Confirms without asking, e.g. for `--assume-yes`.
+pub struct AlwaysYes;
+
+impl Confirmer for AlwaysYes {
+    fn confirm(&self, _: &str) -> bool {
+        true
+    }
+}
+
+/// Declines without asking.
+pub struct AlwaysNo;
+
+impl Confirmer for AlwaysNo {
+    fn confirm(&self, _: &str) -> bool {
+        false
+    }
+}
@@ -2,6 +2,7 @@ use std::borrow::Cow;
 use std::path::PathBuf;
 
 use rari_doc::error::{DocError, UrlError};
+use rari_sitemap::SitemapError;
 use rari_types::error::EnvError;
 use rari_types::locale::LocaleError;
 use rari_utils::error::RariIoError;
@@ -35,6 +36,8 @@ pub enum ToolError {
     #[error(transparent)]
     JsonError(#[from] serde_json::Error),
     #[error(transparent)]
+    SitemapError(#[from] SitemapError),
+    #[error(transparent)]
     YamlError(#[from] yaml_parser::SyntaxError),
     #[error("Invalid Redirection: {0}")]
     InvalidRedirectionEntry(String),
@@ -58,6 +61,8 @@ pub enum ToolError {
     HasSubpagesError(Cow<'static, str>),
     #[error("Target directory ({0}) for slug ({1}) already exists")]
     TargetDirExists(PathBuf, String),
+    #[error("Target docs already exist: {}", .0.join(", "))]
+    ConflictingTargets(Vec<String>),
 
     #[error("Unknown error")]
     Unknown(&'static str),
@@ -2,6 +2,8 @@ use std::ffi::OsStr;
 use std::path::Path;
 use std::process::{Command, Output};
 
+use crate::error::ToolError;
+
 /// Run `git` with `args` with `root` as current directory.
 /// For tests this will run the first arg as command, eg.:
 /// Instead of `git mv foo bar` -> `mv foo bar`.
@@ -20,6 +22,33 @@ pub fn exec_git_with_test_fallback(args: &[impl AsRef<OsStr>], root: impl AsRef<
     exec_git_internal(command, args, root)
 }
 
+/// Run `git mv from to` with `root` as current directory. Like `mv`, `from`
+/// is moved into `to` if `to` is an existing directory.
+/// For tests this renames with the fs api instead, as tests do not run in a
+/// git repository and there is no `mv` to fall back to on Windows.
+pub fn git_mv(from: &Path, to: &Path, root: impl AsRef<Path>) -> Result<(), ToolError> {
+    let root = root.as_ref();
+    if cfg!(test) {
+        let mut target = root.join(to);
+        if target.is_dir() {
+            if let Some(file_name) = from.file_name() {
+                target.push(file_name);
+            }
+        }
+        std::fs::rename(root.join(from), target)?;
+        return Ok(());
+    }
+
+    let output = exec_git(&[OsStr::new("mv"), from.as_os_str(), to.as_os_str()], root);
+    if !output.status.success() {
+        return Err(ToolError::GitError(format!(
+            "Failed to move files: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(())
+}
+
 pub fn exec_git(args: &[impl AsRef<OsStr>], root: impl AsRef<Path>) -> Output {
     let output = Command::new("git")
         .args(args)
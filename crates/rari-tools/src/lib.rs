@@ -1,13 +1,19 @@
 pub mod add_redirect;
+pub mod check_links;
+pub mod confirm;
 pub mod error;
 pub mod fix;
 pub mod git;
 pub mod history;
 pub mod inventory;
 pub mod r#move;
+pub mod preview;
 pub mod redirects;
 pub mod remove;
 pub mod sidebars;
+pub mod sitemap;
+pub mod slug_consistency;
+pub mod slugify;
 pub mod sync_translated_content;
 #[cfg(test)]
 pub mod tests;
@@ -1,12 +1,10 @@
 use std::borrow::Cow;
-use std::ffi::OsStr;
-use std::fs::create_dir_all;
-use std::path::PathBuf;
+use std::fs::{create_dir_all, remove_dir};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use console::{style, Style};
-use dialoguer::theme::ColorfulTheme;
-use dialoguer::Confirm;
 use rari_doc::{
     helpers::subpages::get_sub_pages,
     pages::page::{self, Page, PageCategory, PageLike, PageWriter},
@@ -14,19 +12,47 @@ use rari_doc::{
     utils::root_for_locale,
 };
 use rari_types::locale::Locale;
+use rari_utils::concat_strs;
 
+use crate::confirm::Confirmer;
 use crate::error::ToolError;
-use crate::git::exec_git_with_test_fallback;
-use crate::redirects::add_redirects;
+use crate::git;
+use crate::redirects::{add_redirects, add_redirects_with_prefix};
 use crate::sidebars::update_sidebars;
 use crate::utils::parent_slug;
 use crate::wikihistory::update_wiki_history;
 
+/// Options changing how [`r#move`] moves documents.
+#[derive(Debug, Default, Clone)]
+pub struct MoveOptions {
+    /// Move into a destination that already has some of the documents (e.g.
+    /// from a partial earlier move). Conflicting documents stay in place and
+    /// only the others are moved.
+    pub merge: bool,
+    /// Also move the existing translations of all moved documents.
+    pub all_locales: bool,
+    /// Refuse to move documents out of their top-level section, i.e. when
+    /// the first segments of the old and new slug differ (`Web` vs `Learn`).
+    pub restrict_top_level: bool,
+    /// Update the title of moved documents whose title is derived from the
+    /// last slug segment (`Some_Page` -> `Some Page`) to the new segment.
+    /// Custom titles are kept.
+    pub update_title: bool,
+    /// Also record the old slug of every moved document in its `aliases`
+    /// frontmatter. Redirects from the old URLs are added as usual.
+    pub alias: bool,
+    /// The URL segment the docs are served under, used for the redirects of
+    /// moved documents (`/en-US/<prefix>/<slug>`). `None` means `docs`.
+    /// Redirects under another prefix go to `_redirects.<prefix>.txt`.
+    pub redirect_prefix: Option<String>,
+}
+
 pub fn r#move(
     old_slug: &str,
     new_slug: &str,
     locale: Option<Locale>,
-    assume_yes: bool,
+    options: &MoveOptions,
+    confirmer: &dyn Confirmer,
 ) -> Result<(), ToolError> {
     validate_args(old_slug, new_slug)?;
     let locale = locale.unwrap_or_default();
@@ -35,7 +61,7 @@ pub fn r#move(
     let green = Style::new().green();
     let red = Style::new().red();
     let bold = Style::new().bold();
-    let changes = do_move(old_slug, new_slug, locale, true)?;
+    let changes = do_move(old_slug, new_slug, locale, true, options)?;
     if changes.is_empty() {
         tracing::info!("{}", style("No changes would be made").green());
         return Ok(());
@@ -49,29 +75,47 @@ pub fn r#move(
             green.apply_to("to"),
             green.apply_to(new_slug)
         );
+        for (old_slug, new_slug) in &changes {
+            tracing::info!("{} -> {}", red.apply_to(old_slug), green.apply_to(new_slug));
+        }
+    }
+
+    let translations = if options.all_locales {
+        do_move_translations(&changes, locale, true, options)?
+    } else {
+        vec![]
+    };
+    for (translated_locale, changes) in &translations {
+        tracing::info!(
+            "{} {} {}",
+            green.apply_to("This will move"),
+            bold.apply_to(changes.len()),
+            green.apply_to(format!("documents in {translated_locale}")),
+        );
         for (old_slug, new_slug) in changes {
-            tracing::info!(
-                "{} -> {}",
-                red.apply_to(&old_slug),
-                green.apply_to(&new_slug)
-            );
+            tracing::info!("{} -> {}", red.apply_to(old_slug), green.apply_to(new_slug));
         }
     }
 
-    if assume_yes
-        || Confirm::with_theme(&ColorfulTheme::default())
-            .with_prompt("Proceed?")
-            .default(true)
-            .interact()
-            .unwrap_or_default()
-    {
-        let moved = do_move(old_slug, new_slug, locale, false)?;
+    if confirmer.confirm("Proceed?") {
+        let moved = do_move(old_slug, new_slug, locale, false, options)?;
         tracing::info!(
             "{} {} {}",
             green.apply_to("Moved"),
             bold.apply_to(moved.len()),
             green.apply_to("documents"),
         );
+        if options.all_locales {
+            for (translated_locale, moved) in do_move_translations(&moved, locale, false, options)?
+            {
+                tracing::info!(
+                    "{} {} {}",
+                    green.apply_to("Moved"),
+                    bold.apply_to(moved.len()),
+                    green.apply_to(format!("documents in {translated_locale}")),
+                );
+            }
+        }
     } else {
         return Ok(());
     }
@@ -79,67 +123,263 @@ pub fn r#move(
     Ok(())
 }
 
-fn do_move(
+/// Returns the number of documents moving `old_slug` to `new_slug` would move,
+/// without planning the move itself. Only the doc's folder is walked for
+/// `index.md` files, so no subpages are parsed. This is the length of the dry
+/// run of [`r#move`], as long as none of the targets exist yet.
+pub fn count_move(old_slug: &str, new_slug: &str, locale: Locale) -> Result<usize, ToolError> {
+    validate_args(old_slug, new_slug)?;
+    let old_url = build_url(old_slug, locale, PageCategory::Doc)?;
+    let doc = page::Page::from_url_with_fallback(&old_url)?;
+    if doc.slug() == new_slug {
+        return Ok(0);
+    }
+    let folder = doc
+        .full_path()
+        .parent()
+        .ok_or(ToolError::Unknown("Could not determine folder of doc"))?;
+    count_index_files(folder)
+}
+
+/// Counts the `index.md` files in `folder` and all folders below it.
+fn count_index_files(folder: &Path) -> Result<usize, ToolError> {
+    let mut count = 0;
+    for entry in folder.read_dir()? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            count += count_index_files(&entry.path())?;
+        } else if entry.file_name() == "index.md" {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// The outcome of merging a move into an existing destination.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MergeReport {
+    /// The moved `(old_slug, new_slug)` pairs.
+    pub merged: Vec<(String, String)>,
+    /// The `(old_slug, new_slug)` pairs left in place, as their target exists.
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Moves `old_slug` to `new_slug` in merge mode (see [`MoveOptions::merge`],
+/// which is implied) and reports which documents were merged and which were
+/// skipped. With `dry_run` set, this only reports what would happen.
+pub fn merge_move(
     old_slug: &str,
     new_slug: &str,
     locale: Locale,
     dry_run: bool,
+    options: &MoveOptions,
+) -> Result<MergeReport, ToolError> {
+    let plan = do_move_planned(
+        old_slug,
+        new_slug,
+        locale,
+        dry_run,
+        &MoveOptions {
+            merge: true,
+            ..options.clone()
+        },
+    )?;
+    Ok(MergeReport {
+        merged: plan.pairs,
+        skipped: plan.skipped,
+    })
+}
+
+/// Moves only the `selected_children` of `source_slug`, with their subpages,
+/// below `new_parent_slug`. The source document and its other children stay
+/// in place.
+///
+/// Every selected child must be a direct child of `source_slug`. Redirects
+/// are added for all moved documents. Returns the moved
+/// `(old_slug, new_slug)` pairs.
+pub fn move_subtree(
+    source_slug: &str,
+    selected_children: &[String],
+    new_parent_slug: &str,
+    locale: Locale,
 ) -> Result<Vec<(String, String)>, ToolError> {
-    let old_url = build_url(old_slug, locale, PageCategory::Doc)?;
-    let doc = page::Page::from_url_with_fallback(&old_url)?;
-    let real_old_slug = doc.slug();
+    let source_url = build_url(source_slug, locale, PageCategory::Doc)?;
+    let children = get_sub_pages(&source_url, Some(1), Default::default())?;
+    let selected = selected_children
+        .iter()
+        .map(|selected| {
+            children
+                .iter()
+                .map(|child| child.slug())
+                .find(|slug| slug.eq_ignore_ascii_case(selected))
+                .ok_or_else(|| {
+                    ToolError::InvalidSlug(Cow::Owned(format!(
+                        "{selected} is not a child of {source_slug}"
+                    )))
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
-    let new_parent_slug = parent_slug(new_slug)?;
-    if !page::Page::exists(&build_url(new_parent_slug, locale, PageCategory::Doc)?) {
-        return Err(ToolError::InvalidSlug(Cow::Owned(format!(
-            "new parent slug does not exist: {new_parent_slug}"
-        ))));
+    let mut moved = vec![];
+    for child_slug in selected {
+        let name = child_slug.rsplit('/').next().unwrap_or(child_slug);
+        let new_slug = format!("{}/{name}", new_parent_slug.trim_end_matches('/'));
+        moved.extend(do_move(
+            child_slug,
+            &new_slug,
+            locale,
+            false,
+            &MoveOptions::default(),
+        )?);
     }
-    let subpages = get_sub_pages(&old_url, None, Default::default())?;
-
-    let is_new_slug = real_old_slug != new_slug;
+    Ok(moved)
+}
 
-    // Return early if we move onto ourselves.
-    if !is_new_slug {
-        return Ok(vec![]);
-    }
+/// Moved `(old_slug, new_slug)` pairs per locale.
+type LocaleMoves = Vec<(Locale, Vec<(String, String)>)>;
 
-    let old_folder_path = slug_to_repo_folder_path(real_old_slug, locale)?;
-    let new_folder_path = slug_to_repo_folder_path(new_slug, locale)?;
+/// Moves the translations of the documents moved in `locale` the same way.
+///
+/// For every locale other than `locale` the translated counterpart of each
+/// `(old_slug, new_slug)` pair is moved along with its subpages. Missing
+/// translations are skipped, as are translations whose new parent has no
+/// translation. Returns the moved pairs per locale.
+fn do_move_translations(
+    pairs: &[(String, String)],
+    locale: Locale,
+    dry_run: bool,
+    options: &MoveOptions,
+) -> Result<LocaleMoves, ToolError> {
+    // Parents first, moving a parent moves all its subpages.
+    let mut pairs = pairs.iter().collect::<Vec<_>>();
+    pairs.sort_by_key(|(old_slug, _)| old_slug.matches('/').count());
 
-    if root_for_locale(locale)?
-        .join(&new_folder_path)
-        .try_exists()?
+    let mut reports = vec![];
+    for &translated_locale in Locale::translated()
+        .iter()
+        .filter(|translated_locale| **translated_locale != locale)
     {
-        return Err(ToolError::TargetDirExists(
-            new_folder_path,
-            new_slug.to_string(),
-        ));
+        let mut moved: Vec<(String, String)> = vec![];
+        for (old_slug, new_slug) in &pairs {
+            if moved
+                .iter()
+                .any(|(moved_slug, _)| old_slug.starts_with(&format!("{moved_slug}/")))
+            {
+                continue;
+            }
+            if !page::Page::exists(&build_url(old_slug, translated_locale, PageCategory::Doc)?) {
+                continue;
+            }
+            let new_parent_slug = parent_slug(new_slug)?;
+            if !page::Page::exists(&build_url(
+                new_parent_slug,
+                translated_locale,
+                PageCategory::Doc,
+            )?) {
+                tracing::warn!(
+                    "Skipping {old_slug} in {translated_locale}: new parent {new_parent_slug} has no translation"
+                );
+                continue;
+            }
+            moved.extend(do_move(
+                old_slug,
+                new_slug,
+                translated_locale,
+                dry_run,
+                options,
+            )?);
+        }
+        if !moved.is_empty() {
+            reports.push((translated_locale, moved));
+        }
     }
+    Ok(reports)
+}
 
-    let pairs = [doc.clone()]
-        .iter()
-        .chain(&subpages)
-        .map(|page_ref| {
-            let slug = page_ref.slug().to_owned();
-            let new_slug = slug.replace(real_old_slug, new_slug);
-            (slug, new_slug)
-        })
-        .collect::<Vec<_>>();
+fn do_move(
+    old_slug: &str,
+    new_slug: &str,
+    locale: Locale,
+    dry_run: bool,
+    options: &MoveOptions,
+) -> Result<Vec<(String, String)>, ToolError> {
+    Ok(do_move_planned(old_slug, new_slug, locale, dry_run, options)?.pairs)
+}
+
+/// Like [`do_move`], returning the whole plan including the pairs skipped
+/// with [`MoveOptions::merge`].
+fn do_move_planned(
+    old_slug: &str,
+    new_slug: &str,
+    locale: Locale,
+    dry_run: bool,
+    options: &MoveOptions,
+) -> Result<MovePlan, ToolError> {
+    let _span = tracing::debug_span!("do_move", old_slug, new_slug, %locale, dry_run).entered();
+    let plan = simulate_move(old_slug, new_slug, locale, options, &ContentRoot)?;
+
+    if !options.merge && !plan.pairs.is_empty() {
+        let new_folder_path = slug_to_repo_folder_path(new_slug, locale)?;
+        if root_for_locale(locale)?
+            .join(&new_folder_path)
+            .try_exists()?
+        {
+            return Err(ToolError::TargetDirExists(
+                new_folder_path,
+                new_slug.to_string(),
+            ));
+        }
+    }
 
     // Return early for a dry run.
-    if dry_run {
-        return Ok(pairs);
+    if !dry_run {
+        apply_move(&plan, locale, options)?;
+    }
+    Ok(plan)
+}
+
+/// Carries out a move planned by [`simulate_move`].
+fn apply_move(plan: &MovePlan, locale: Locale, options: &MoveOptions) -> Result<(), ToolError> {
+    let MovePlan {
+        old_slug: real_old_slug,
+        new_slug,
+        pairs,
+        ..
+    } = plan;
+    if pairs.is_empty() {
+        return Ok(());
     }
+    let (real_old_slug, new_slug) = (real_old_slug.as_str(), new_slug.as_str());
+    let old_folder_path = slug_to_repo_folder_path(real_old_slug, locale)?;
+    let new_folder_path = slug_to_repo_folder_path(new_slug, locale)?;
+
+    let pages_to_move = pairs
+        .iter()
+        .map(|(old_slug, _)| {
+            let url = build_url(old_slug, locale, PageCategory::Doc)?;
+            Ok(page::Page::from_url(&url)?)
+        })
+        .collect::<Result<Vec<_>, ToolError>>()?;
 
-    // No dry run, so build a vec of pairs of `(old_page, Option<new_doc>)`.
-    let doc_pairs = [&doc].into_iter().chain(&subpages).filter_map(|page_ref| {
+    // Build a vec of pairs of `(old_page, Option<new_doc>)`.
+    let doc_pairs = pages_to_move.iter().filter_map(|page_ref| {
         let slug = page_ref.slug().to_owned();
-        let new_slug = slug.replace(real_old_slug, new_slug);
-        let new_page = page_ref.clone();
+        let new_slug = rewrite_slug_prefix(&slug, real_old_slug, new_slug);
+        let new_page = (*page_ref).clone();
         if let Page::Doc(doc) = new_page {
             let mut cloned_doc = doc.clone();
             let doc = Arc::make_mut(&mut cloned_doc);
+            if options.update_title {
+                if let Some(title) = updated_title(&doc.meta.title, &slug, &new_slug) {
+                    doc.meta.title = title;
+                }
+            }
+            if options.alias {
+                doc.meta.aliases.retain(|alias| alias != &new_slug);
+                if !doc.meta.aliases.contains(&slug) {
+                    doc.meta.aliases.push(slug);
+                }
+            }
             doc.meta.slug = new_slug.to_string();
             Some(doc.to_owned())
         } else {
@@ -151,49 +391,96 @@ fn do_move(
     // Now iterate through the vec and write the new frontmatter
     // (the changed slug) to all affected documents (root + children).
     // The docs are all still in their old location at this time.
+    tracing::debug!(phase = "frontmatter");
     for new_doc in doc_pairs {
         new_doc.write()?;
     }
 
-    // Now we use the git command to move the whole parent directory
-    // to a new location. This will move all children as well and
-    // makes sure that we get a proper "file moved" in the git history.
-
-    // Make sure the target parent directory exists.
-    if let Some(target_parent_path) = new_folder_path.parent() {
-        let absolute_target_parent_path = root_for_locale(locale)?.join(target_parent_path);
-        create_dir_all(absolute_target_parent_path)?;
+    tracing::debug!(phase = "git_mv");
+    if options.merge {
+        // The destination exists already, so we move document by document.
+        // A document whose folder is not at the destination yet is moved
+        // with its whole folder, taking its assets and subpages along.
+        // Otherwise its files are moved one by one, except for those
+        // already at the destination.
+        let root = root_for_locale(locale)?;
+        let mut folders = pairs
+            .iter()
+            .map(|(old_slug, new_slug)| {
+                Ok((
+                    slug_to_repo_folder_path(old_slug, locale)?,
+                    slug_to_repo_folder_path(new_slug, locale)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, ToolError>>()?;
+        folders.sort_by_key(|(from, _)| from.components().count());
+        let mut moved_folders: Vec<PathBuf> = vec![];
+        for (from, to) in folders {
+            if moved_folders.iter().any(|moved| from.starts_with(moved)) {
+                continue;
+            }
+            if !root.join(&to).exists() {
+                if let Some(parent) = to.parent() {
+                    create_dir_all(root.join(parent))?;
+                }
+                git_mv(&from, &to, locale)?;
+                moved_folders.push(from);
+                continue;
+            }
+            for entry in root.join(&from).read_dir()? {
+                let entry = entry?;
+                let file_name = entry.file_name();
+                if entry.file_type()?.is_file() && !root.join(&to).join(&file_name).exists() {
+                    git_mv(&from.join(&file_name), &to.join(&file_name), locale)?;
+                }
+            }
+        }
+        // Clean up the folders we emptied, deepest first. Folders that still
+        // contain conflicting documents or other files are kept.
+        let mut old_folders = pairs
+            .iter()
+            .map(|(old_slug, _)| slug_to_repo_folder_path(old_slug, locale))
+            .collect::<Result<Vec<_>, _>>()?;
+        old_folders.sort_by_key(|folder| std::cmp::Reverse(folder.components().count()));
+        for folder in old_folders {
+            let path = root_for_locale(locale)?.join(folder);
+            match remove_dir(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == ErrorKind::NotFound => {}
+                // `ErrorKind::DirectoryNotEmpty` is newer than our MSRV.
+                Err(_) if path.read_dir()?.next().is_some() => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
     } else {
-        return Err(ToolError::Unknown(
-            "Could not determine parent path for new folder",
-        ));
-    }
+        // Now we use the git command to move the whole parent directory
+        // to a new location. This will move all children as well and
+        // makes sure that we get a proper "file moved" in the git history.
 
-    // Execute the git move.
-    let output = exec_git_with_test_fallback(
-        &[
-            OsStr::new("mv"),
-            old_folder_path.as_os_str(),
-            new_folder_path.as_os_str(),
-        ],
-        root_for_locale(locale)?,
-    );
+        // Make sure the target parent directory exists.
+        if let Some(target_parent_path) = new_folder_path.parent() {
+            let absolute_target_parent_path = root_for_locale(locale)?.join(target_parent_path);
+            create_dir_all(absolute_target_parent_path)?;
+        } else {
+            return Err(ToolError::Unknown(
+                "Could not determine parent path for new folder",
+            ));
+        }
 
-    if !output.status.success() {
-        return Err(ToolError::GitError(format!(
-            "Failed to move files: {}",
-            String::from_utf8_lossy(&output.stderr)
-        )));
+        // Execute the git move.
+        git_mv(&old_folder_path, &new_folder_path, locale)?;
     }
 
     // Update Wiki history for entries that have an entry for the old slug.
-    update_wiki_history(locale, &pairs)?;
+    tracing::debug!(phase = "wiki_history");
+    update_wiki_history(locale, pairs)?;
 
     // Update the sidebars, changing links and paths where necessary.
     // But only for the default locale. Translated content cannot change
     // sidebars. Map the pairs from (String, String) to (String, Option<String>)
     // to match the function signature.
     if locale == Locale::default() {
+        tracing::debug!(phase = "sidebars");
         update_sidebars(
             &pairs
                 .iter()
@@ -208,25 +495,231 @@ fn do_move(
     }
 
     // Update the redirect map. Create pairs of URLs from the slug pairs.
+    tracing::debug!(phase = "redirects");
     let url_pairs = pairs
         .iter()
         .map(|(old_slug, new_slug)| {
-            let old_url = build_url(old_slug, locale, PageCategory::Doc)?;
-            let new_url = build_url(new_slug, locale, PageCategory::Doc)?;
+            let old_url = redirect_url(old_slug, locale, options)?;
+            let new_url = redirect_url(new_slug, locale, options)?;
             Ok((old_url, new_url))
         })
         .collect::<Result<Vec<_>, ToolError>>()?;
-    add_redirects(locale, &url_pairs)?;
+    match &options.redirect_prefix {
+        Some(prefix) => add_redirects_with_prefix(locale, &url_pairs, prefix)?,
+        None => add_redirects(locale, &url_pairs)?,
+    }
+
+    Ok(())
+}
+
+/// The URL of the doc at `slug` for a redirect, under the `redirect_prefix`
+/// of `options` if set.
+fn redirect_url(slug: &str, locale: Locale, options: &MoveOptions) -> Result<String, ToolError> {
+    Ok(match &options.redirect_prefix {
+        Some(prefix) => concat_strs!("/", locale.as_url_str(), "/", prefix, "/", slug),
+        None => build_url(slug, locale, PageCategory::Doc)?,
+    })
+}
+
+/// Read access to the docs of a locale, as needed to plan a move.
+///
+/// [`simulate_move`] only goes through this trait, so moves can be planned
+/// against content that is not the configured content root.
+pub trait MoveContent {
+    /// Returns the slug of the doc at `slug` as written in its frontmatter,
+    /// or `None` if there is no such doc.
+    fn doc_slug(&self, slug: &str, locale: Locale) -> Result<Option<String>, ToolError>;
+    /// Returns the slugs of all descendants of the doc at `slug`.
+    fn sub_page_slugs(&self, slug: &str, locale: Locale) -> Result<Vec<String>, ToolError>;
+}
+
+/// The docs in the configured content root, which [`r#move`] moves.
+pub struct ContentRoot;
+
+impl MoveContent for ContentRoot {
+    fn doc_slug(&self, slug: &str, locale: Locale) -> Result<Option<String>, ToolError> {
+        let url = build_url(slug, locale, PageCategory::Doc)?;
+        if !page::Page::exists(&url) {
+            return Ok(None);
+        }
+        Ok(Some(page::Page::from_url(&url)?.slug().to_string()))
+    }
+
+    fn sub_page_slugs(&self, slug: &str, locale: Locale) -> Result<Vec<String>, ToolError> {
+        let url = build_url(slug, locale, PageCategory::Doc)?;
+        Ok(get_sub_pages(&url, None, Default::default())?
+            .iter()
+            .map(|page_ref| page_ref.slug().to_string())
+            .collect())
+    }
+}
+
+/// A move planned by [`simulate_move`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct MovePlan {
+    /// The slug of the moved doc as written in its frontmatter.
+    pub old_slug: String,
+    pub new_slug: String,
+    /// The `(old_slug, new_slug)` pairs of the documents to move, empty if
+    /// the doc is moved onto itself.
+    pub pairs: Vec<(String, String)>,
+    /// The `(old_slug, new_slug)` pairs left in place with
+    /// [`MoveOptions::merge`], as their target exists.
+    pub skipped: Vec<(String, String)>,
+}
+
+/// Plans moving `old_slug` to `new_slug` without touching any files.
+///
+/// This runs the same checks as a dry run of [`r#move`], except for the on disk
+/// check of the target folder.
+pub fn simulate_move(
+    old_slug: &str,
+    new_slug: &str,
+    locale: Locale,
+    options: &MoveOptions,
+    content: &dyn MoveContent,
+) -> Result<MovePlan, ToolError> {
+    tracing::debug!(phase = "validate");
+    validate_args(old_slug, new_slug)?;
+    let real_old_slug = content.doc_slug(old_slug, locale)?.ok_or_else(|| {
+        ToolError::InvalidSlug(Cow::Owned(format!("doc does not exist: {old_slug}")))
+    })?;
+
+    let new_parent_exists = content.doc_slug(parent_slug(new_slug)?, locale)?.is_some();
+    validate_move(&real_old_slug, new_slug, new_parent_exists, options)?;
+    tracing::debug!(phase = "subpages");
+    let subpages = content.sub_page_slugs(&real_old_slug, locale)?;
+
+    let mut plan = MovePlan {
+        old_slug: real_old_slug,
+        new_slug: new_slug.to_string(),
+        ..Default::default()
+    };
+    // Return early if we move onto ourselves.
+    if plan.old_slug == new_slug {
+        return Ok(plan);
+    }
+
+    let pairs = slug_pairs(
+        &plan.old_slug,
+        new_slug,
+        std::iter::once(plan.old_slug.as_str()).chain(subpages.iter().map(String::as_str)),
+    );
+
+    // Check every single target, a partial earlier move may have left some
+    // of the documents at the destination already.
+    for pair in pairs {
+        if content.doc_slug(&pair.1, locale)?.is_some() {
+            plan.skipped.push(pair);
+        } else {
+            plan.pairs.push(pair);
+        }
+    }
+    if !plan.skipped.is_empty() {
+        if !options.merge {
+            return Err(ToolError::ConflictingTargets(
+                plan.skipped
+                    .into_iter()
+                    .map(|(_, new_slug)| new_slug)
+                    .collect(),
+            ));
+        }
+        for (old_slug, new_slug) in &plan.skipped {
+            tracing::warn!("Skipping {old_slug}: target {new_slug} already exists");
+        }
+    }
+    Ok(plan)
+}
+
+/// Checks that the doc at `real_old_slug` can be moved to `new_slug`, given
+/// whether the new parent exists: the doc cannot move below itself, which
+/// would create a cyclic folder structure, or out of its top-level section
+/// with [`MoveOptions::restrict_top_level`].
+fn validate_move(
+    real_old_slug: &str,
+    new_slug: &str,
+    new_parent_exists: bool,
+    options: &MoveOptions,
+) -> Result<(), ToolError> {
+    if options.restrict_top_level {
+        let top_level = |slug: &str| slug.split('/').next().unwrap_or_default().to_lowercase();
+        if top_level(real_old_slug) != top_level(new_slug) {
+            return Err(ToolError::InvalidSlug(Cow::Owned(format!(
+                "cannot move {real_old_slug} to another top-level section: {new_slug}"
+            ))));
+        }
+    }
+    let new_parent_slug = parent_slug(new_slug)?;
+    if !new_parent_exists {
+        return Err(ToolError::InvalidSlug(Cow::Owned(format!(
+            "new parent slug does not exist: {new_parent_slug}"
+        ))));
+    }
+    let mut ancestor = new_parent_slug;
+    loop {
+        if ancestor.eq_ignore_ascii_case(real_old_slug) {
+            return Err(ToolError::InvalidSlug(Cow::Owned(format!(
+                "cannot move {real_old_slug} below itself: {new_slug}"
+            ))));
+        }
+        match parent_slug(ancestor) {
+            Ok(parent) => ancestor = parent,
+            Err(_) => break,
+        }
+    }
+    Ok(())
+}
+
+fn slug_pairs<'a>(
+    real_old_slug: &str,
+    new_slug: &str,
+    slugs: impl IntoIterator<Item = &'a str>,
+) -> Vec<(String, String)> {
+    slugs
+        .into_iter()
+        .map(|slug| {
+            (
+                slug.to_string(),
+                rewrite_slug_prefix(slug, real_old_slug, new_slug),
+            )
+        })
+        .collect()
+}
+
+/// The title derived from the last segment of `slug`, `_` becoming spaces.
+fn derived_title(slug: &str) -> String {
+    slug.rsplit('/').next().unwrap_or(slug).replace('_', " ")
+}
+
+/// Returns the new title for a document moving from `old_slug` to `new_slug`
+/// if `title` is derived from `old_slug` and the derived title changes.
+fn updated_title(title: &str, old_slug: &str, new_slug: &str) -> Option<String> {
+    let new_title = derived_title(new_slug);
+    (title == derived_title(old_slug) && title != new_title).then_some(new_title)
+}
+
+/// Replaces `old_prefix` at the start of `slug` with `new_prefix`, if `slug`
+/// is `old_prefix` itself or one of its sub pages. Otherwise `slug` is
+/// returned unchanged, so moving `Web/API` leaves `Web/APIExample` alone.
+pub fn rewrite_slug_prefix(slug: &str, old_prefix: &str, new_prefix: &str) -> String {
+    match slug.strip_prefix(old_prefix) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            format!("{new_prefix}{rest}")
+        }
+        _ => slug.to_string(),
+    }
+}
 
-    // finally, return the pairs of old and new slugs
-    Ok(pairs)
+fn git_mv(from: &Path, to: &Path, locale: Locale) -> Result<(), ToolError> {
+    git::git_mv(from, to, root_for_locale(locale)?)
 }
 
 fn slug_to_repo_folder_path(slug: &str, locale: Locale) -> Result<PathBuf, ToolError> {
     let mut new_folder_path = PathBuf::from(locale.as_folder_str());
     let url = build_url(slug, locale, PageCategory::Doc)?;
     let UrlMeta { folder_path, .. } = url_meta_from(&url)?;
-    new_folder_path.push(folder_path);
+    // Push component by component to get the platform's separators.
+    new_folder_path.extend(folder_path.components());
     Ok(new_folder_path)
 }
 
@@ -265,7 +758,17 @@ use serial_test::file_serial;
 #[file_serial(file_fixtures)]
 mod test {
 
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use tracing::field::{Field, Visit};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    use tracing_subscriber::Registry;
+
     use super::*;
+    use crate::confirm::{AlwaysNo, AlwaysYes};
+    use crate::redirects::{prefixed_redirects_path, read_redirects_raw};
     use crate::tests::fixtures::docs::DocFixtures;
     use crate::tests::fixtures::redirects::RedirectFixtures;
     use crate::tests::fixtures::sidebars::SidebarFixtures;
@@ -294,6 +797,167 @@ mod test {
         assert_eq!(parent_slug("a/b/c/").unwrap(), "a/b");
     }
 
+    #[test]
+    fn test_slug_to_repo_folder_path() {
+        assert_eq!(
+            slug_to_repo_folder_path("Web/API/ExampleOne", Locale::EnUs).unwrap(),
+            ["en-us", "web", "api", "exampleone"]
+                .iter()
+                .collect::<PathBuf>()
+        );
+    }
+
+    #[test]
+    fn test_rewrite_slug_prefix() {
+        assert_eq!(
+            rewrite_slug_prefix("Web/API", "Web/API", "Web/Foo"),
+            "Web/Foo"
+        );
+        assert_eq!(
+            rewrite_slug_prefix("Web/API/Window", "Web/API", "Web/Foo"),
+            "Web/Foo/Window"
+        );
+        assert_eq!(
+            rewrite_slug_prefix("Web/APIExample", "Web/API", "Web/Foo"),
+            "Web/APIExample"
+        );
+        assert_eq!(
+            rewrite_slug_prefix("Learn/Web/API", "Web/API", "Web/Foo"),
+            "Learn/Web/API"
+        );
+    }
+
+    struct InMemoryContent(Vec<&'static str>);
+
+    impl MoveContent for InMemoryContent {
+        fn doc_slug(&self, slug: &str, _: Locale) -> Result<Option<String>, ToolError> {
+            Ok(self
+                .0
+                .iter()
+                .find(|s| s.eq_ignore_ascii_case(slug))
+                .map(|s| s.to_string()))
+        }
+
+        fn sub_page_slugs(&self, slug: &str, _: Locale) -> Result<Vec<String>, ToolError> {
+            let prefix = format!("{}/", slug.to_lowercase());
+            Ok(self
+                .0
+                .iter()
+                .filter(|s| s.to_lowercase().starts_with(&prefix))
+                .map(|s| s.to_string())
+                .collect())
+        }
+    }
+
+    #[test]
+    fn test_simulate_move() {
+        let content = InMemoryContent(vec![
+            "Web",
+            "Web/API",
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOne/SubExampleOne",
+            "Web/API/ExampleOne/SubExampleOne/Deep",
+            "Web/API/Other",
+            "Web/API/Other/SubExampleOne",
+        ]);
+
+        let plan = simulate_move(
+            "web/api/exampleone",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+            &MoveOptions::default(),
+            &content,
+        )
+        .unwrap();
+        assert_eq!(plan.old_slug, "Web/API/ExampleOne");
+        assert!(plan.skipped.is_empty());
+        assert_eq!(
+            plan.pairs,
+            vec![
+                (s("Web/API/ExampleOne"), s("Web/API/ExampleOneNewLocation")),
+                (
+                    s("Web/API/ExampleOne/SubExampleOne"),
+                    s("Web/API/ExampleOneNewLocation/SubExampleOne")
+                ),
+                (
+                    s("Web/API/ExampleOne/SubExampleOne/Deep"),
+                    s("Web/API/ExampleOneNewLocation/SubExampleOne/Deep")
+                ),
+            ]
+        );
+
+        let result = simulate_move(
+            "Web/API/ExampleOne",
+            "Web/CSS/ExampleOne",
+            Locale::EnUs,
+            &MoveOptions::default(),
+            &content,
+        );
+        assert!(matches!(result, Err(ToolError::InvalidSlug(_))));
+
+        let result = simulate_move(
+            "Web/API/ExampleOne/SubExampleOne",
+            "Web/API/Other/SubExampleOne",
+            Locale::EnUs,
+            &MoveOptions::default(),
+            &content,
+        );
+        assert!(matches!(
+            result,
+            Err(ToolError::ConflictingTargets(conflicts)) if conflicts == vec![s("Web/API/Other/SubExampleOne")]
+        ));
+
+        let plan = simulate_move(
+            "Web/API/ExampleOne",
+            "Web/API/Other",
+            Locale::EnUs,
+            &MoveOptions {
+                merge: true,
+                ..Default::default()
+            },
+            &content,
+        )
+        .unwrap();
+        assert_eq!(
+            plan.pairs,
+            vec![(
+                s("Web/API/ExampleOne/SubExampleOne/Deep"),
+                s("Web/API/Other/SubExampleOne/Deep")
+            )]
+        );
+        assert_eq!(
+            plan.skipped,
+            vec![
+                (s("Web/API/ExampleOne"), s("Web/API/Other")),
+                (
+                    s("Web/API/ExampleOne/SubExampleOne"),
+                    s("Web/API/Other/SubExampleOne")
+                ),
+            ]
+        );
+
+        let result = simulate_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOne/SubExampleOne/ExampleOne",
+            Locale::EnUs,
+            &MoveOptions::default(),
+            &content,
+        );
+        assert!(matches!(result, Err(ToolError::InvalidSlug(_))));
+
+        let result = simulate_move(
+            "Web/API/ExampleOne",
+            "Learn/ExampleOne",
+            Locale::EnUs,
+            &MoveOptions {
+                restrict_top_level: true,
+                ..Default::default()
+            },
+            &InMemoryContent(vec!["Web/API/ExampleOne", "Learn"]),
+        );
+        assert!(matches!(result, Err(ToolError::InvalidSlug(_))));
+    }
+
     #[test]
     fn test_do_move_dry_run() {
         let slugs = vec![
@@ -314,6 +978,7 @@ mod test {
             "Web/API/ExampleOneNewLocation",
             Locale::EnUs,
             true,
+            &MoveOptions::default(),
         );
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -381,6 +1046,7 @@ mod test {
             "Web/API/ExampleOneNewLocation",
             Locale::EnUs,
             false,
+            &MoveOptions::default(),
         );
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -449,6 +1115,695 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_count_move() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne/Deep".to_string(),
+            "Web/API/ExampleOne/SubExampleTwo".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let pairs = do_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+            true,
+            &MoveOptions::default(),
+        )
+        .unwrap();
+        let count = count_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+        )
+        .unwrap();
+        assert_eq!(count, 4);
+        assert_eq!(count, pairs.len());
+        assert_eq!(
+            count_move("Web/API/ExampleOne", "Web/API/ExampleOne", Locale::EnUs).unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_do_move_update_title() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleTwo".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let root_path = root_for_locale(Locale::EnUs).unwrap();
+        let custom = root_path.join("en-us/web/api/exampletwo/index.md");
+        let content = std::fs::read_to_string(&custom).unwrap();
+        std::fs::write(
+            &custom,
+            content.replace("title: ExampleTwo", "title: A custom title"),
+        )
+        .unwrap();
+
+        let options = MoveOptions {
+            update_title: true,
+            ..Default::default()
+        };
+        do_move(
+            "Web/API/ExampleOne",
+            "Web/API/Renamed_One",
+            Locale::EnUs,
+            false,
+            &options,
+        )
+        .unwrap();
+        do_move(
+            "Web/API/ExampleTwo",
+            "Web/API/RenamedTwo",
+            Locale::EnUs,
+            false,
+            &options,
+        )
+        .unwrap();
+
+        let title = |slug: &str| {
+            let url = build_url(slug, Locale::EnUs, PageCategory::Doc).unwrap();
+            Page::from_url(&url).unwrap().title().to_string()
+        };
+        assert_eq!(title("Web/API/Renamed_One"), "Renamed One");
+        assert_eq!(title("Web/API/RenamedTwo"), "A custom title");
+    }
+
+    #[test]
+    fn test_do_move_alias() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![(
+            "docs/Web/API/SomethingThatPointsToAMovedDoc".to_string(),
+            "docs/Web/API/ExampleOne/SubExampleOne".to_string(),
+        )];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        do_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+            false,
+            &MoveOptions {
+                alias: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        let aliases = |slug: &str| {
+            let url = build_url(slug, Locale::EnUs, PageCategory::Doc).unwrap();
+            match Page::from_url(&url).unwrap() {
+                Page::Doc(doc) => doc.meta.aliases.clone(),
+                _ => panic!("not a doc: {slug}"),
+            }
+        };
+        assert_eq!(
+            aliases("Web/API/ExampleOneNewLocation"),
+            vec![s("Web/API/ExampleOne")]
+        );
+        assert_eq!(
+            aliases("Web/API/ExampleOneNewLocation/SubExampleOne"),
+            vec![s("Web/API/ExampleOne/SubExampleOne")]
+        );
+
+        let redirects = get_redirects_map(Locale::EnUs);
+        assert_eq!(
+            redirects.get("/en-US/docs/Web/API/ExampleOne").unwrap(),
+            "/en-US/docs/Web/API/ExampleOneNewLocation"
+        );
+        assert_eq!(
+            redirects
+                .get("/en-US/docs/Web/API/ExampleOne/SubExampleOne")
+                .unwrap(),
+            "/en-US/docs/Web/API/ExampleOneNewLocation/SubExampleOne"
+        );
+        assert_eq!(
+            redirects
+                .get("/en-US/docs/Web/API/SomethingThatPointsToAMovedDoc")
+                .unwrap(),
+            "/en-US/docs/Web/API/ExampleOneNewLocation/SubExampleOne"
+        );
+    }
+
+    #[test]
+    fn test_do_move_redirect_prefix() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        do_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+            false,
+            &MoveOptions {
+                redirect_prefix: Some(s("reference")),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert!(get_redirects_map(Locale::EnUs).is_empty());
+        let path = prefixed_redirects_path(Locale::EnUs, "reference").unwrap();
+        let redirects = read_redirects_raw(&path)
+            .unwrap()
+            .into_iter()
+            .collect::<HashMap<_, _>>();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(redirects.len(), 2);
+        assert_eq!(
+            redirects
+                .get("/en-US/reference/Web/API/ExampleOne")
+                .unwrap(),
+            "/en-US/reference/Web/API/ExampleOneNewLocation"
+        );
+        assert_eq!(
+            redirects
+                .get("/en-US/reference/Web/API/ExampleOne/SubExampleOne")
+                .unwrap(),
+            "/en-US/reference/Web/API/ExampleOneNewLocation/SubExampleOne"
+        );
+    }
+
+    #[test]
+    fn test_do_move_conflicting() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleTwo".to_string(),
+            "Web/API/ExampleOneNewLocation/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let result = do_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+            true,
+            &MoveOptions::default(),
+        );
+        match result {
+            Err(ToolError::ConflictingTargets(conflicts)) => assert_eq!(
+                conflicts,
+                vec![
+                    s("Web/API/ExampleOneNewLocation"),
+                    s("Web/API/ExampleOneNewLocation/SubExampleOne")
+                ]
+            ),
+            other => panic!("expected conflicting targets, got {other:?}"),
+        }
+
+        let result = do_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+            false,
+            &MoveOptions {
+                merge: true,
+                ..Default::default()
+            },
+        );
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(
+            result,
+            vec![(
+                s("Web/API/ExampleOne/SubExampleTwo"),
+                s("Web/API/ExampleOneNewLocation/SubExampleTwo")
+            )]
+        );
+
+        let root_path = root_for_locale(Locale::EnUs).unwrap();
+        let should_exist = vec![
+            "en-us/web/api/exampleone",
+            "en-us/web/api/exampleone/subexampleone",
+            "en-us/web/api/exampleonenewlocation/subexampleone",
+            "en-us/web/api/exampleonenewlocation/subexampletwo",
+        ];
+        let should_not_exist = vec!["en-us/web/api/exampleone/subexampletwo"];
+        check_file_existence(root_path, &should_exist, &should_not_exist);
+
+        let redirects = get_redirects_map(Locale::EnUs);
+        assert_eq!(redirects.len(), 1);
+        assert_eq!(
+            redirects
+                .get("/en-US/docs/Web/API/ExampleOne/SubExampleTwo")
+                .unwrap(),
+            "/en-US/docs/Web/API/ExampleOneNewLocation/SubExampleTwo"
+        );
+    }
+
+    #[test]
+    fn test_merge_move() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleTwo".to_string(),
+            "Web/API/ExampleOne/SubExampleThree".to_string(),
+            "Web/API/ExampleTwo".to_string(),
+            "Web/API/ExampleTwo/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+        // Assets stay with their documents. The folder of SubExampleTwo is at
+        // the destination already, SubExampleThree is moved as a whole.
+        let root_path = root_for_locale(Locale::EnUs).unwrap();
+        let en_us = root_path.join("en-us/web/api");
+        for asset in [
+            "exampleone/image.png",
+            "exampleone/subexampletwo/image.png",
+            "exampleone/subexamplethree/image.png",
+            "exampletwo/subexampletwo/other.png",
+        ] {
+            let path = en_us.join(asset);
+            create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let expected = MergeReport {
+            merged: vec![
+                (
+                    s("Web/API/ExampleOne/SubExampleThree"),
+                    s("Web/API/ExampleTwo/SubExampleThree"),
+                ),
+                (
+                    s("Web/API/ExampleOne/SubExampleTwo"),
+                    s("Web/API/ExampleTwo/SubExampleTwo"),
+                ),
+            ],
+            skipped: vec![
+                (s("Web/API/ExampleOne"), s("Web/API/ExampleTwo")),
+                (
+                    s("Web/API/ExampleOne/SubExampleOne"),
+                    s("Web/API/ExampleTwo/SubExampleOne"),
+                ),
+            ],
+        };
+        let report = merge_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleTwo",
+            Locale::EnUs,
+            true,
+            &MoveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report, expected);
+
+        let report = merge_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleTwo",
+            Locale::EnUs,
+            false,
+            &MoveOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report, expected);
+
+        let should_exist = vec![
+            "en-us/web/api/exampleone",
+            "en-us/web/api/exampleone/image.png",
+            "en-us/web/api/exampleone/subexampleone",
+            "en-us/web/api/exampletwo",
+            "en-us/web/api/exampletwo/subexampleone",
+            "en-us/web/api/exampletwo/subexampletwo/index.md",
+            "en-us/web/api/exampletwo/subexampletwo/image.png",
+            "en-us/web/api/exampletwo/subexampletwo/other.png",
+            "en-us/web/api/exampletwo/subexamplethree/index.md",
+            "en-us/web/api/exampletwo/subexamplethree/image.png",
+        ];
+        let should_not_exist = vec![
+            "en-us/web/api/exampleone/subexampletwo",
+            "en-us/web/api/exampleone/subexamplethree",
+        ];
+        check_file_existence(root_path, &should_exist, &should_not_exist);
+    }
+
+    /// Records the `phase` of every event.
+    struct PhaseRecorder(Arc<Mutex<Vec<String>>>);
+
+    impl<S: Subscriber> Layer<S> for PhaseRecorder {
+        fn on_event(&self, event: &Event<'_>, _: Context<'_, S>) {
+            struct PhaseVisitor(Option<String>);
+            impl Visit for PhaseVisitor {
+                fn record_str(&mut self, field: &Field, value: &str) {
+                    if field.name() == "phase" {
+                        self.0 = Some(value.to_string());
+                    }
+                }
+                fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+            }
+            let mut visitor = PhaseVisitor(None);
+            event.record(&mut visitor);
+            if let Some(phase) = visitor.0 {
+                self.0.lock().unwrap().push(phase);
+            }
+        }
+    }
+
+    #[test]
+    fn test_do_move_phases() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let phases = Arc::new(Mutex::new(vec![]));
+        let subscriber = Registry::default().with(PhaseRecorder(phases.clone()));
+        let result = tracing::subscriber::with_default(subscriber, || {
+            do_move(
+                "Web/API/ExampleOne",
+                "Web/API/ExampleOneNewLocation",
+                Locale::EnUs,
+                false,
+                &MoveOptions::default(),
+            )
+        });
+        assert!(result.is_ok());
+        assert_eq!(
+            *phases.lock().unwrap(),
+            vec![
+                "validate",
+                "subpages",
+                "frontmatter",
+                "git_mv",
+                "wiki_history",
+                "sidebars",
+                "redirects"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_do_move_restrict_top_level() {
+        let slugs = vec!["Web/API/ExampleOne".to_string(), "Learn".to_string()];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let result = do_move(
+            "Web/API/ExampleOne",
+            "Learn/ExampleOne",
+            Locale::EnUs,
+            true,
+            &MoveOptions {
+                restrict_top_level: true,
+                ..Default::default()
+            },
+        );
+        assert!(matches!(result, Err(ToolError::InvalidSlug(_))));
+
+        let result = do_move(
+            "Web/API/ExampleOne",
+            "Learn/ExampleOne",
+            Locale::EnUs,
+            true,
+            &MoveOptions::default(),
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec![(s("Web/API/ExampleOne"), s("Learn/ExampleOne"))]
+        );
+    }
+
+    #[test]
+    fn test_do_move_below_itself() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let result = do_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOne/SubExampleOne/ExampleOne",
+            Locale::EnUs,
+            false,
+            &MoveOptions::default(),
+        );
+        assert!(matches!(result, Err(ToolError::InvalidSlug(_))));
+
+        let root_path = root_for_locale(Locale::EnUs).unwrap();
+        let should_exist = vec![
+            "en-us/web/api/exampleone",
+            "en-us/web/api/exampleone/subexampleone",
+        ];
+        check_file_existence(root_path, &should_exist, &[]);
+    }
+
+    #[test]
+    fn test_move_subtree() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne/Deep".to_string(),
+            "Web/API/ExampleOne/SubExampleTwo".to_string(),
+            "Web/API/ExampleTwo".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let result = move_subtree(
+            "Web/API/ExampleOne",
+            &[s("Web/API/ExampleOne/SubExampleOne")],
+            "Web/API/ExampleTwo",
+            Locale::EnUs,
+        );
+        assert_eq!(
+            result.unwrap(),
+            vec![
+                (
+                    s("Web/API/ExampleOne/SubExampleOne"),
+                    s("Web/API/ExampleTwo/SubExampleOne")
+                ),
+                (
+                    s("Web/API/ExampleOne/SubExampleOne/Deep"),
+                    s("Web/API/ExampleTwo/SubExampleOne/Deep")
+                ),
+            ]
+        );
+
+        let root_path = root_for_locale(Locale::EnUs).unwrap();
+        let should_exist = vec![
+            "en-us/web/api/exampleone",
+            "en-us/web/api/exampleone/subexampletwo",
+            "en-us/web/api/exampletwo/subexampleone",
+            "en-us/web/api/exampletwo/subexampleone/deep",
+        ];
+        let should_not_exist = vec!["en-us/web/api/exampleone/subexampleone"];
+        check_file_existence(root_path, &should_exist, &should_not_exist);
+
+        let redirects = get_redirects_map(Locale::EnUs);
+        assert_eq!(redirects.len(), 2);
+        assert_eq!(
+            redirects
+                .get("/en-US/docs/Web/API/ExampleOne/SubExampleOne")
+                .unwrap(),
+            "/en-US/docs/Web/API/ExampleTwo/SubExampleOne"
+        );
+
+        let result = move_subtree(
+            "Web/API/ExampleOne",
+            &[s("Web/API/ExampleTwo")],
+            "Web/API/ExampleTwo",
+            Locale::EnUs,
+        );
+        assert!(matches!(result, Err(ToolError::InvalidSlug(_))));
+    }
+
+    #[test]
+    fn test_move_confirmed() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let result = r#move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            None,
+            &MoveOptions::default(),
+            &AlwaysYes,
+        );
+        assert!(result.is_ok());
+
+        let root_path = root_for_locale(Locale::EnUs).unwrap();
+        let should_exist = vec![
+            "en-us/web/api/exampleonenewlocation",
+            "en-us/web/api/exampleonenewlocation/subexampleone",
+        ];
+        let should_not_exist = vec![
+            "en-us/web/api/exampleone",
+            "en-us/web/api/exampleone/subexampleone",
+        ];
+        check_file_existence(root_path, &should_exist, &should_not_exist);
+        assert_eq!(get_redirects_map(Locale::EnUs).len(), 2);
+    }
+
+    #[test]
+    fn test_move_declined() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _sidebars = SidebarFixtures::default();
+
+        let result = r#move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            None,
+            &MoveOptions::default(),
+            &AlwaysNo,
+        );
+        assert!(result.is_ok());
+
+        let root_path = root_for_locale(Locale::EnUs).unwrap();
+        let should_exist = vec![
+            "en-us/web/api/exampleone",
+            "en-us/web/api/exampleone/subexampleone",
+        ];
+        let should_not_exist = vec![
+            "en-us/web/api/exampleonenewlocation",
+            "en-us/web/api/exampleonenewlocation/subexampleone",
+        ];
+        check_file_existence(root_path, &should_exist, &should_not_exist);
+        assert!(get_redirects_map(Locale::EnUs).is_empty());
+    }
+
+    #[test]
+    fn test_do_move_all_locales() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleTwo".to_string(),
+        ];
+        // The translation is missing SubExampleTwo.
+        let translated_slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _translated_docs = DocFixtures::new(&translated_slugs, Locale::PtBr);
+        let _wikihistory = WikihistoryFixtures::new(&slugs, Locale::EnUs);
+        let _translated_wikihistory = WikihistoryFixtures::new(&translated_slugs, Locale::PtBr);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        let _translated_redirects = RedirectFixtures::new(&redirects, Locale::PtBr);
+        let _sidebars = SidebarFixtures::default();
+
+        let options = MoveOptions {
+            all_locales: true,
+            ..Default::default()
+        };
+        let moved = do_move(
+            "Web/API/ExampleOne",
+            "Web/API/ExampleOneNewLocation",
+            Locale::EnUs,
+            false,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(moved.len(), 3);
+
+        let result = do_move_translations(&moved, Locale::EnUs, false, &options).unwrap();
+        assert_eq!(
+            result,
+            vec![(
+                Locale::PtBr,
+                vec![
+                    (s("Web/API/ExampleOne"), s("Web/API/ExampleOneNewLocation")),
+                    (
+                        s("Web/API/ExampleOne/SubExampleOne"),
+                        s("Web/API/ExampleOneNewLocation/SubExampleOne")
+                    ),
+                ]
+            )]
+        );
+
+        let should_exist = vec![
+            "en-us/web/api/exampleonenewlocation/subexampleone",
+            "en-us/web/api/exampleonenewlocation/subexampletwo",
+        ];
+        let should_not_exist = vec!["en-us/web/api/exampleone"];
+        check_file_existence(
+            root_for_locale(Locale::EnUs).unwrap(),
+            &should_exist,
+            &should_not_exist,
+        );
+        let should_exist = vec![
+            "pt-br/web/api/exampleonenewlocation",
+            "pt-br/web/api/exampleonenewlocation/subexampleone",
+        ];
+        let should_not_exist = vec![
+            "pt-br/web/api/exampleone",
+            "pt-br/web/api/exampleonenewlocation/subexampletwo",
+        ];
+        check_file_existence(
+            root_for_locale(Locale::PtBr).unwrap(),
+            &should_exist,
+            &should_not_exist,
+        );
+
+        let redirects = get_redirects_map(Locale::PtBr);
+        assert_eq!(
+            redirects.get("/pt-BR/docs/Web/API/ExampleOne").unwrap(),
+            "/pt-BR/docs/Web/API/ExampleOneNewLocation"
+        );
+    }
+
     #[test]
     fn test_do_move_translated() {
         let slugs = vec![
@@ -492,6 +1847,7 @@ mod test {
             "Web/API/ExampleOneNewLocation",
             Locale::PtBr,
             false,
+            &MoveOptions::default(),
         );
         assert!(result.is_ok());
         let result = result.unwrap();
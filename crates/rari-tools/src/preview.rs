@@ -0,0 +1,66 @@
+use std::io::Write;
+
+use rari_doc::pages::page::{Page, PageCategory, PageLike};
+use rari_doc::resolve::build_url;
+use rari_types::locale::Locale;
+
+use crate::error::ToolError;
+
+/// Renders the markdown body of the doc at `slug` to HTML, for a quick look
+/// at the output of the markdown renderer.
+///
+/// Only the markdown is rendered, macros are left as they are and no
+/// post-processing of the full build is applied.
+pub fn render_slug(slug: &str, locale: Locale) -> Result<String, ToolError> {
+    let url = build_url(slug, locale, PageCategory::Doc)?;
+    let page = Page::from_url(&url)?;
+    Ok(page.render()?)
+}
+
+/// Prints the HTML of the doc at `slug` to stdout, see [`render_slug`].
+pub fn preview_slug(slug: &str, locale: Locale) -> Result<(), ToolError> {
+    let html = render_slug(slug, locale)?;
+    std::io::stdout().write_all(html.as_bytes())?;
+    Ok(())
+}
+
+// These tests use file system fixtures to simulate content and translated content.
+// The file system is a shared resource, so we force tests to be run serially,
+// to avoid concurrent fixture management issues.
+// Using `file_serial` as a synchronization lock, we should be able to run all tests
+// using the same `key` (here: file_fixtures) to be serialized across modules.
+#[cfg(test)]
+use serial_test::file_serial;
+#[cfg(test)]
+#[file_serial(file_fixtures)]
+mod test {
+    use std::fs;
+
+    use rari_doc::utils::root_for_locale;
+
+    use super::*;
+    use crate::tests::fixtures::docs::DocFixtures;
+
+    #[test]
+    fn test_render_slug() {
+        let slugs = vec!["Web/API/ExampleOne".to_string()];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let path = root_for_locale(Locale::EnUs)
+            .unwrap()
+            .join("en-us/web/api/exampleone/index.md");
+        fs::write(
+            &path,
+            "---\ntitle: ExampleOne\nslug: Web/API/ExampleOne\n---\n\n\
+            Some *example* text.\n\n## Syntax\n\n```js\nexample();\n```\n",
+        )
+        .unwrap();
+
+        let html = render_slug("Web/API/ExampleOne", Locale::EnUs).unwrap();
+        assert_eq!(
+            html,
+            "<p data-sourcepos=\"2:1-2:20\">Some <em data-sourcepos=\"2:6-2:14\">example</em> text.</p>\n\
+            <h2 id=\"syntax\" data-sourcepos=\"4:1-4:9\">Syntax</h2>\n\
+            <pre class=\"brush: js notranslate\" data-sourcepos=\"6:1-8:3\">example();\n</pre>\n"
+        );
+    }
+}
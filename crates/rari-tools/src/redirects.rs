@@ -1,7 +1,7 @@
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::fs::File;
+use std::fs::{self, File};
 use std::io::{self, BufRead, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -26,6 +26,9 @@ const REDIRECT_FILE_HEADER: &str = r#"# DO NOT EDIT THIS FILE MANUALLY.
 
 static FORBIDDEN_URL_SYMBOLS: [char; 2] = ['\t', '\n'];
 
+/// The URL segment docs are served under, `/<locale>/docs/<slug>`.
+const DOCS_PREFIX: &str = "docs";
+
 /// Determines the final target of a redirect by traversing the redirect graph.
 ///
 /// This function recursively follows redirects from a starting point `s` using the provided
@@ -205,20 +208,38 @@ pub fn short_cuts(
 /// - **ReadRedirectsError**: Returned if there's an error reading the existing redirects from the `_redirects.txt` file.
 /// - *Additional errors can be added based on further implementations and validations.*
 pub fn add_redirects(locale: Locale, update_pairs: &[(String, String)]) -> Result<(), ToolError> {
+    add_redirects_with_prefix(locale, update_pairs, DOCS_PREFIX)
+}
+
+/// Like [`add_redirects`], for docs served under `/<locale>/<prefix>/`
+/// instead of `/<locale>/docs/`.
+///
+/// Redirects under a prefix other than `docs` are kept in their own
+/// `_redirects.<prefix>.txt` next to `_redirects.txt`, which only holds
+/// `/docs/` redirects. They are validated as if they were under `/docs/`,
+/// i.e. the docs they point to have to exist in the content.
+pub fn add_redirects_with_prefix(
+    locale: Locale,
+    update_pairs: &[(String, String)],
+    prefix: &str,
+) -> Result<(), ToolError> {
     // read the redirect map for the locale
     // we do not use REDIRECTS since it is static and has all the locales
 
     // Read the redirects file for the locale and populate the map.
     let mut pairs = HashMap::new();
-    let path = redirects_path(locale)?;
-
-    match read_redirects_raw(&path) {
-        Ok(iter) => pairs.extend(iter),
-        Err(e) => {
-            error!("Error reading redirects: {e}");
-            return Err(ToolError::ReadRedirectsError(e.to_string()));
-        }
-    };
+    let path = prefixed_redirects_path(locale, prefix)?;
+
+    // A prefix gets its redirects file with its first redirects.
+    if prefix == DOCS_PREFIX || path.try_exists()? {
+        match read_redirects_raw(&path) {
+            Ok(iter) => pairs.extend(iter),
+            Err(e) => {
+                error!("Error reading redirects: {e}");
+                return Err(ToolError::ReadRedirectsError(e.to_string()));
+            }
+        };
+    }
 
     // Separate the pairs into case-only changes and proper redirects
     let (case_changed_targets, new_pairs) = separate_case_changes(update_pairs);
@@ -235,7 +256,7 @@ pub fn add_redirects(locale: Locale, update_pairs: &[(String, String)]) -> Resul
 
     let clean_pairs: HashMap<String, String> = short_cuts(&clean_pairs)?.into_iter().collect();
 
-    validate_pairs(&clean_pairs, locale)?;
+    validate_pairs(&clean_pairs, locale, prefix)?;
 
     // Write the updated map back to the redirects file
     write_redirects(&path, &clean_pairs)?;
@@ -296,13 +317,364 @@ pub fn remove_redirects_by_targets(locale: Locale, targets: &[String]) -> Result
         return Ok(());
     }
 
-    validate_pairs(&pairs, locale)?;
+    validate_pairs(&pairs, locale, DOCS_PREFIX)?;
 
     write_redirects(&path, &pairs)?;
 
     Ok(())
 }
 
+/// Resolves the final redirect target for a URL.
+///
+/// Reads the `_redirects.txt` file for `locale` and follows the redirect chain
+/// starting at `url` until it reaches a URL that is not redirected any further.
+/// Lookups are case-insensitive, like the redirect handling when building.
+///
+/// Fragments are handled per hop:
+/// - A source with a fragment (`/en-US/docs/A#old`) only matches that exact
+///   fragment, which is consumed by the match.
+/// - Otherwise the source is matched without the fragment. The fragment is
+///   carried over to the target, unless the target has a fragment of its own.
+///
+/// # Arguments
+///
+/// * `url` - The URL to resolve, e.g. `/en-US/docs/Web/API/Something`.
+/// * `locale` - The locale whose redirects file is used.
+///
+/// # Returns
+///
+/// - `Some(target)` with the final target if `url` is redirected.
+/// - `None` if `url` is not redirected, the redirects cannot be read, or the
+///   chain contains a cycle.
+pub fn resolve_redirect(url: &str, locale: Locale) -> Option<String> {
+    let path = redirects_path(locale).ok()?;
+    let pairs = match read_redirects_raw(&path) {
+        Ok(iter) => iter
+            .into_iter()
+            .map(|(from, to)| (from.to_lowercase(), to))
+            .collect::<HashMap<_, _>>(),
+        Err(e) => {
+            error!("Error reading redirects: {e}");
+            return None;
+        }
+    };
+    let next = |current: &str| -> Option<String> {
+        if let Some(to) = pairs.get(&current.to_lowercase()) {
+            return Some(to.clone());
+        }
+        let (bare, hash) = current.split_once('#')?;
+        let to = pairs.get(&bare.to_lowercase())?;
+        Some(if to.contains('#') {
+            to.clone()
+        } else {
+            concat_strs!(to, "#", hash)
+        })
+    };
+
+    let mut seen = HashSet::new();
+    let mut current = url.to_string();
+    while let Some(to) = next(&current) {
+        if !seen.insert(current.to_lowercase()) {
+            warn!("Redirect cycle detected while resolving {url}");
+            return None;
+        }
+        current = to;
+    }
+    if seen.is_empty() {
+        None
+    } else {
+        Some(current)
+    }
+}
+
+/// A change made by [`repair_redirects`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectRepair {
+    pub from: String,
+    /// The dangling target.
+    pub to: String,
+    /// The fallback the redirect points to now, `None` if it was removed.
+    pub new_to: Option<String>,
+}
+
+/// Repairs redirects whose targets do not exist anymore, e.g. because pages
+/// were deleted outside of rari.
+///
+/// Dangling redirects are repointed to `fallback` if given, and removed otherwise.
+///
+/// # Arguments
+///
+/// * `locale` - The locale whose `_redirects.txt` is repaired.
+/// * `fallback` - An optional URL to redirect to instead of the missing pages.
+///
+/// # Returns
+///
+/// - `Ok(Vec<RedirectRepair>)` with the changes made, sorted by `from`.
+/// - `Err(ToolError)` if the fallback is not a valid target, the redirects
+///   cannot be read or written, or the repaired redirects are invalid.
+pub fn repair_redirects(
+    locale: Locale,
+    fallback: Option<&str>,
+) -> Result<Vec<RedirectRepair>, ToolError> {
+    if let Some(fallback) = fallback {
+        validate_to_url(fallback, locale)?;
+    }
+
+    let mut pairs = HashMap::new();
+    let path = redirects_path(locale)?;
+    match read_redirects_raw(&path) {
+        Ok(iter) => pairs.extend(iter),
+        Err(e) => {
+            error!("Error reading redirects: {e}");
+            return Err(ToolError::ReadRedirectsError(e.to_string()));
+        }
+    };
+
+    let mut repairs = vec![];
+    pairs.retain(|from, to| {
+        if !is_dangling_to_url(to) {
+            return true;
+        }
+        // Never turn a dangling redirect into a redirect to itself.
+        let new_to = fallback.filter(|fallback| !fallback.eq_ignore_ascii_case(from));
+        repairs.push(RedirectRepair {
+            from: from.clone(),
+            to: to.clone(),
+            new_to: new_to.map(String::from),
+        });
+        match new_to {
+            Some(new_to) => {
+                *to = new_to.to_string();
+                true
+            }
+            None => false,
+        }
+    });
+
+    if repairs.is_empty() {
+        return Ok(repairs);
+    }
+    repairs.sort_by(|a, b| a.from.cmp(&b.from));
+
+    validate_pairs(&pairs, locale, DOCS_PREFIX)?;
+    write_redirects(&path, &pairs)?;
+
+    Ok(repairs)
+}
+
+/// A row of a redirect import which was not imported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedRedirect {
+    /// The 1-based line in the import file.
+    pub line: usize,
+    /// The raw content of the line.
+    pub row: String,
+    pub reason: String,
+}
+
+/// Imports redirects from a two-column file of `from` and `to` URLs,
+/// separated by a tab or a comma. Empty lines and lines starting with `#`
+/// are skipped.
+///
+/// Each row is validated on its own: both URLs must be valid for `locale`, a
+/// `from` may only be imported once, and the redirect must not form a cycle
+/// with the existing redirects or earlier rows. Targets which are redirected
+/// themselves are followed to their final target. Valid rows are merged into
+/// the redirects of `locale` via [`add_redirects`].
+///
+/// # Arguments
+///
+/// * `locale` - The locale whose `_redirects.txt` the redirects are added to.
+/// * `path` - The file to import.
+///
+/// # Returns
+///
+/// - `Ok(Vec<RejectedRedirect>)` with the rows which were not imported.
+/// - `Err(ToolError)` if the import file or the existing redirects cannot be
+///   read, or writing the merged redirects fails.
+pub fn import_redirects(locale: Locale, path: &Path) -> Result<Vec<RejectedRedirect>, ToolError> {
+    let mut known: HashMap<String, String> = match read_redirects_raw(&redirects_path(locale)?) {
+        Ok(iter) => iter
+            .into_iter()
+            .map(|(from, to)| (from.to_lowercase(), to))
+            .collect(),
+        Err(e) => {
+            error!("Error reading redirects: {e}");
+            return Err(ToolError::ReadRedirectsError(e.to_string()));
+        }
+    };
+
+    let mut imported = HashSet::new();
+    let mut accepted = vec![];
+    let mut rejected = vec![];
+    for (i, line) in read_lines(path)?.enumerate() {
+        let row = line?;
+        let trimmed = row.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let separator = if trimmed.contains('\t') { '\t' } else { ',' };
+        let columns: Vec<&str> = trimmed.split(separator).map(str::trim).collect();
+        let checked = match columns.as_slice() {
+            [from, to] => check_import_pair(from, to, locale, &known, &imported),
+            _ => Err(format!("expected 2 columns, found {}", columns.len())),
+        };
+        let to = match checked {
+            Ok(to) => to,
+            Err(reason) => {
+                rejected.push(RejectedRedirect {
+                    line: i + 1,
+                    row,
+                    reason,
+                });
+                continue;
+            }
+        };
+        let from = columns[0].to_string();
+        imported.insert(from.to_lowercase());
+        known.insert(from.to_lowercase(), to.clone());
+        accepted.push((from, to));
+    }
+
+    if !accepted.is_empty() {
+        add_redirects(locale, &accepted)?;
+    }
+    Ok(rejected)
+}
+
+/// Checks a single imported `from` -> `to` pair. Returns the final target
+/// after following existing redirects, or the reason to reject the pair.
+fn check_import_pair(
+    from: &str,
+    to: &str,
+    locale: Locale,
+    known: &HashMap<String, String>,
+    imported: &HashSet<String>,
+) -> Result<String, String> {
+    validate_from_url(from, locale).map_err(|e| e.to_string())?;
+    if imported.contains(&from.to_lowercase()) {
+        return Err(format!("duplicate redirect for {from}"));
+    }
+    let mut seen = HashSet::from([from.to_lowercase()]);
+    let mut target = to;
+    while let Some(next) = known.get(&target.to_lowercase()) {
+        if !seen.insert(target.to_lowercase()) {
+            break;
+        }
+        target = next;
+    }
+    if seen.contains(&target.to_lowercase()) {
+        return Err(format!("redirect cycle {from} -> {to}"));
+    }
+    validate_to_url(target, locale).map_err(|e| e.to_string())?;
+    Ok(target.to_string())
+}
+
+/// The outcome of [`rebuild_redirects`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RebuildReport {
+    /// Redirects in the rebuilt file.
+    pub kept: usize,
+    /// Kept redirects which now point to the end of their chain.
+    pub flattened: usize,
+    /// Redirects removed because a later line redirects the same URL.
+    pub duplicates: usize,
+    /// Redirects removed because their chain runs into a cycle.
+    pub cyclic: usize,
+    /// Redirects removed because their final target does not exist.
+    pub dangling: usize,
+}
+
+impl RebuildReport {
+    /// The number of redirects removed.
+    pub fn removed(&self) -> usize {
+        self.duplicates + self.cyclic + self.dangling
+    }
+}
+
+/// Rebuilds the redirects file of `locale` from scratch.
+///
+/// Duplicate redirects are removed, keeping the last one, as well as
+/// redirects running into a cycle. Chains are flattened with [`short_cuts`],
+/// then redirects whose final target does not exist are removed. The result
+/// is written sorted, replacing the file atomically.
+///
+/// # Arguments
+///
+/// * `locale` - The locale whose `_redirects.txt` is rebuilt.
+///
+/// # Returns
+///
+/// - `Ok(RebuildReport)` with the number of redirects kept and removed.
+/// - `Err(ToolError)` if the redirects cannot be read or written, or the
+///   rebuilt redirects are invalid.
+pub fn rebuild_redirects(locale: Locale) -> Result<RebuildReport, ToolError> {
+    let path = redirects_path(locale)?;
+    let raw = match read_redirects_raw(&path) {
+        Ok(iter) => iter,
+        Err(e) => {
+            error!("Error reading redirects: {e}");
+            return Err(ToolError::ReadRedirectsError(e.to_string()));
+        }
+    };
+
+    let mut report = RebuildReport::default();
+    let mut unique: HashMap<String, (String, String)> = HashMap::new();
+    for (from, to) in raw {
+        if unique.insert(from.to_lowercase(), (from, to)).is_some() {
+            report.duplicates += 1;
+        }
+    }
+
+    let dag: HashMap<String, String> = unique
+        .iter()
+        .map(|(from, (_, to))| (from.clone(), to.to_lowercase()))
+        .collect();
+    let is_cyclic = |from: &str| {
+        let mut seen = HashSet::from([from]);
+        let mut current = from;
+        while let Some(next) = dag.get(current) {
+            if !seen.insert(next) {
+                return true;
+            }
+            current = next;
+        }
+        false
+    };
+    let pairs: HashMap<String, String> = unique
+        .iter()
+        .filter(|(from, _)| {
+            let cyclic = is_cyclic(from);
+            if cyclic {
+                report.cyclic += 1;
+            }
+            !cyclic
+        })
+        .map(|(_, pair)| pair.clone())
+        .collect();
+
+    let mut flattened = HashMap::new();
+    for (from, to) in short_cuts(&pairs)? {
+        if pairs.get(&from) != Some(&to) {
+            report.flattened += 1;
+        }
+        flattened.insert(from, to);
+    }
+    flattened.retain(|_, to| {
+        let dangling = is_dangling_to_url(to);
+        if dangling {
+            report.dangling += 1;
+        }
+        !dangling
+    });
+    report.kept = flattened.len();
+
+    validate_pairs(&flattened, locale, DOCS_PREFIX)?;
+    write_redirects(&path, &flattened)?;
+
+    Ok(report)
+}
+
 /// Optimizes and rewrites redirect rules for supported locales.
 ///
 /// This function:
@@ -470,7 +842,7 @@ pub fn validate_redirects(locale_filter: Option<&[Locale]>) -> Result<(), ToolEr
             let old_sorted_map = locale_pairs.iter().collect::<BTreeMap<_, _>>();
             if let Some(locale_pairs) = fix_redirects_internal(locale_pairs)?.get(locale) {
                 let new_sorted_map = locale_pairs.clone().into_iter().collect::<BTreeMap<_, _>>();
-                validate_pairs(locale_pairs, *locale)?;
+                validate_pairs(locale_pairs, *locale, DOCS_PREFIX)?;
                 compare_sorted_redirects(old_sorted_map, new_sorted_map)?;
             }
         }
@@ -520,6 +892,18 @@ pub(crate) fn redirects_path(locale: Locale) -> Result<PathBuf, ToolError> {
     Ok(root.join(locale.as_folder_str()).join("_redirects.txt"))
 }
 
+/// Gets the path to the redirects file for docs of a locale served under
+/// `prefix`, `_redirects.txt` for `docs` and `_redirects.<prefix>.txt` otherwise.
+pub(crate) fn prefixed_redirects_path(locale: Locale, prefix: &str) -> Result<PathBuf, ToolError> {
+    if prefix == DOCS_PREFIX {
+        return redirects_path(locale);
+    }
+    let root = root_for_locale(locale)?;
+    Ok(root
+        .join(locale.as_folder_str())
+        .join(concat_strs!("_redirects.", prefix, ".txt")))
+}
+
 /// Validates a list of redirect pairs.
 ///
 /// Iterates through each `(from, to)` pair and validates both URLs based on the locale.
@@ -528,19 +912,42 @@ pub(crate) fn redirects_path(locale: Locale) -> Result<PathBuf, ToolError> {
 ///
 /// * `pairs` - A `HashMap` of redirect pairs.
 /// * `locale` - A reference to the `Locale`
+/// * `prefix` - The URL segment docs are served under, URLs under it are
+///   validated as `/docs/` URLs.
 ///
 /// # Returns
 ///
 /// * `Ok(())` if all pairs are valid.
 /// * `Err(ToolError)` if any pair is invalid.
-fn validate_pairs(pairs: &HashMap<String, String>, locale: Locale) -> Result<(), ToolError> {
+fn validate_pairs(
+    pairs: &HashMap<String, String>,
+    locale: Locale,
+    prefix: &str,
+) -> Result<(), ToolError> {
     for (from, to) in pairs {
-        validate_from_url(from, locale)?;
-        validate_to_url(to, locale)?;
+        validate_from_url(&with_docs_prefix(from, prefix), locale)?;
+        validate_to_url(&with_docs_prefix(to, prefix), locale)?;
     }
     Ok(())
 }
 
+/// Rewrites a `/<locale>/<prefix>/…` URL to `/<locale>/docs/…`, other URLs
+/// are returned as is.
+fn with_docs_prefix<'a>(url: &'a str, prefix: &str) -> Cow<'a, str> {
+    if prefix == DOCS_PREFIX {
+        return Cow::Borrowed(url);
+    }
+    let mut parts = url.splitn(4, '/');
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(""), Some(locale), Some(segment), Some(rest))
+            if segment.eq_ignore_ascii_case(prefix) =>
+        {
+            Cow::Owned(format!("/{locale}/{DOCS_PREFIX}/{rest}"))
+        }
+        _ => Cow::Borrowed(url),
+    }
+}
+
 /// Validates the 'from' URL in a redirect pair.
 ///
 /// Ensures that the URL:
@@ -654,7 +1061,7 @@ fn validate_to_url(url: &str, locale: Locale) -> Result<(), ToolError> {
             folder_path: path, ..
         } = url_meta_from(bare_url)?;
 
-        if !Page::exists(bare_url) {
+        if is_dangling_to_url(url) {
             return Err(ToolError::InvalidRedirectToURL(format!(
                 "To-URL '{}' resolves to a non-existing file/folder at '{}' for locale '{}'.",
                 url,
@@ -683,6 +1090,13 @@ fn validate_to_url(url: &str, locale: Locale) -> Result<(), ToolError> {
     Ok(())
 }
 
+/// Returns `true` if `url` is an internal redirect target without a page.
+fn is_dangling_to_url(url: &str) -> bool {
+    url.starts_with('/')
+        && !is_vanity_redirect_url(url)
+        && !Page::exists(url.split('#').next().unwrap_or(""))
+}
+
 fn is_vanity_redirect_url(url: &str) -> bool {
     url.strip_prefix('/')
         .and_then(|url| url.strip_suffix('/'))
@@ -747,7 +1161,8 @@ pub(crate) fn read_redirects_raw(
 }
 /// Writes the redirects from the HashMap to the specified file path.
 ///
-/// Each redirect is written in the format: `from to`.
+/// Each redirect is written in the format: `from to`. The redirects are
+/// written to a temporary file first, which then replaces the file at `path`.
 ///
 /// # Arguments
 ///
@@ -759,7 +1174,9 @@ pub(crate) fn read_redirects_raw(
 /// * `Ok(())` if the file is written successfully.
 /// * `Err(String)` with an error message if writing fails.
 fn write_redirects(path: &Path, map: &HashMap<String, String>) -> Result<(), ToolError> {
-    let file = File::create(path)?;
+    backup_redirects(path)?;
+    let tmp_path = tmp_path(path);
+    let file = File::create(&tmp_path)?;
     let mut buffed = BufWriter::new(file);
 
     // Sort the Map by making a BTreeMap from the map.
@@ -775,10 +1192,38 @@ fn write_redirects(path: &Path, map: &HashMap<String, String>) -> Result<(), Too
         buffed.write_all(to.as_bytes())?;
         buffed.write_all(b"\n")?;
     }
+    buffed.flush()?;
+    drop(buffed);
+    fs::rename(&tmp_path, path)?;
 
     Ok(())
 }
 
+/// Copies the redirects file to `_redirects.txt.bak` before it gets rewritten,
+/// so a failed operation can be recovered. Only the most recent backup is kept.
+///
+/// Does nothing if the redirects file does not exist yet.
+pub(crate) fn backup_redirects(path: &Path) -> Result<(), ToolError> {
+    if path.try_exists()? {
+        fs::copy(path, backup_path(path))?;
+    }
+    Ok(())
+}
+
+/// The path of the backup of the redirects file at `path`.
+pub(crate) fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// The path of the temporary file the redirects file at `path` is written to.
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    PathBuf::from(tmp)
+}
+
 /// Separates redirect pairs into case-only changes and proper redirects.
 ///
 /// # Arguments
@@ -1383,4 +1828,292 @@ mod tests {
         let res = validate_redirects(Some(&[Locale::EnUs]));
         assert!(matches!(res, Err(ToolError::InvalidRedirectOrder(..))))
     }
+
+    #[test]
+    fn resolve_redirect_direct() {
+        let pairs = vec![(s("docs/A"), s("docs/B"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+        assert_eq!(
+            resolve_redirect("/en-US/docs/A", Locale::EnUs),
+            Some(s("/en-US/docs/B"))
+        );
+        assert_eq!(
+            resolve_redirect("/en-us/docs/a", Locale::EnUs),
+            Some(s("/en-US/docs/B"))
+        );
+    }
+
+    #[test]
+    fn resolve_redirect_source_fragment() {
+        let pairs = vec![(s("docs/A#old"), s("docs/B")), (s("docs/C"), s("docs/D"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+        assert_eq!(
+            resolve_redirect("/en-US/docs/A#old", Locale::EnUs),
+            Some(s("/en-US/docs/B"))
+        );
+        assert_eq!(resolve_redirect("/en-US/docs/A#other", Locale::EnUs), None);
+        assert_eq!(
+            resolve_redirect("/en-US/docs/C#part", Locale::EnUs),
+            Some(s("/en-US/docs/D#part"))
+        );
+    }
+
+    #[test]
+    fn resolve_redirect_target_fragment() {
+        let pairs = vec![(s("docs/A"), s("docs/B#section"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+        assert_eq!(
+            resolve_redirect("/en-US/docs/A", Locale::EnUs),
+            Some(s("/en-US/docs/B#section"))
+        );
+        assert_eq!(
+            resolve_redirect("/en-US/docs/A#part", Locale::EnUs),
+            Some(s("/en-US/docs/B#section"))
+        );
+    }
+
+    #[test]
+    fn resolve_redirect_chained() {
+        // Not written through add_redirects, so the chain is not short-cut.
+        let pairs = vec![(s("docs/A"), s("docs/B")), (s("docs/B"), s("docs/C"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+        assert_eq!(
+            resolve_redirect("/en-US/docs/A", Locale::EnUs),
+            Some(s("/en-US/docs/C"))
+        );
+    }
+
+    #[test]
+    fn resolve_redirect_cycle() {
+        let pairs = vec![(s("docs/A"), s("docs/B")), (s("docs/B"), s("docs/A"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+        assert_eq!(resolve_redirect("/en-US/docs/A", Locale::EnUs), None);
+    }
+
+    #[test]
+    fn resolve_redirect_none() {
+        let pairs = vec![(s("docs/A"), s("docs/B"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+        assert_eq!(resolve_redirect("/en-US/docs/B", Locale::EnUs), None);
+    }
+
+    #[test]
+    fn repair_redirects_fallback() {
+        let _docs = DocFixtures::new(
+            &[s("Web/API/ExampleOne"), s("Web/API/Fallback")],
+            Locale::EnUs,
+        );
+        let pairs = vec![
+            (s("docs/A"), s("docs/Web/API/Gone")),
+            (s("docs/B"), s("docs/Web/API/ExampleOne")),
+        ];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+
+        let repairs = repair_redirects(Locale::EnUs, Some("/en-US/docs/Web/API/Fallback")).unwrap();
+        assert_eq!(
+            repairs,
+            vec![RedirectRepair {
+                from: s("/en-US/docs/A"),
+                to: s("/en-US/docs/Web/API/Gone"),
+                new_to: Some(s("/en-US/docs/Web/API/Fallback")),
+            }]
+        );
+
+        let redirects = crate::utils::get_redirects_map(Locale::EnUs);
+        assert_eq!(redirects.len(), 2);
+        assert_eq!(
+            redirects.get("/en-US/docs/A").unwrap(),
+            "/en-US/docs/Web/API/Fallback"
+        );
+        assert_eq!(
+            redirects.get("/en-US/docs/B").unwrap(),
+            "/en-US/docs/Web/API/ExampleOne"
+        );
+    }
+
+    #[test]
+    fn import_redirects_mixed() {
+        let _docs = DocFixtures::new(
+            &[s("Web/API/ExampleOne"), s("Web/API/ExampleTwo")],
+            Locale::EnUs,
+        );
+        let pairs = vec![(s("docs/Old"), s("docs/Web/API/ExampleOne"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+
+        let path = root_for_locale(Locale::EnUs)
+            .unwrap()
+            .join("import_redirects.tsv");
+        std::fs::write(
+            &path,
+            "# from\tto\n\
+            /en-US/docs/A\t/en-US/docs/Web/API/ExampleOne\n\
+            /en-US/docs/B, /en-US/docs/Old\n\
+            \n\
+            /de/docs/C\t/en-US/docs/Web/API/ExampleOne\n\
+            /en-US/docs/D\t/en-US/docs/Web/API/Gone\n\
+            /en-US/docs/E\n\
+            /en-US/docs/a\t/en-US/docs/Web/API/ExampleTwo\n\
+            /en-US/docs/F\t/en-US/docs/F\n",
+        )
+        .unwrap();
+        let rejected = import_redirects(Locale::EnUs, &path);
+        std::fs::remove_file(&path).unwrap();
+        let rejected = rejected.unwrap();
+
+        assert_eq!(
+            rejected.iter().map(|r| r.line).collect::<Vec<_>>(),
+            vec![5, 6, 7, 8, 9]
+        );
+        assert!(rejected[0]
+            .reason
+            .contains("does not match expected locale"));
+        assert!(rejected[1].reason.contains("non-existing"));
+        assert_eq!(rejected[2].reason, "expected 2 columns, found 1");
+        assert_eq!(rejected[3].reason, "duplicate redirect for /en-US/docs/a");
+        assert_eq!(
+            rejected[4].reason,
+            "redirect cycle /en-US/docs/F -> /en-US/docs/F"
+        );
+
+        let redirects = crate::utils::get_redirects_map(Locale::EnUs);
+        assert_eq!(redirects.len(), 3);
+        assert_eq!(
+            redirects.get("/en-US/docs/A").unwrap(),
+            "/en-US/docs/Web/API/ExampleOne"
+        );
+        assert_eq!(
+            redirects.get("/en-US/docs/B").unwrap(),
+            "/en-US/docs/Web/API/ExampleOne"
+        );
+    }
+
+    #[test]
+    fn add_redirects_backup() {
+        let _docs = DocFixtures::new(
+            &[s("Web/API/ExampleOne"), s("Web/API/ExampleTwo")],
+            Locale::EnUs,
+        );
+        let pairs = vec![(s("docs/A"), s("docs/Web/API/ExampleOne"))];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+
+        let path = redirects_path(Locale::EnUs).unwrap();
+        let before = fs::read_to_string(&path).unwrap();
+        add_redirects(
+            Locale::EnUs,
+            &[(s("/en-US/docs/B"), s("/en-US/docs/Web/API/ExampleTwo"))],
+        )
+        .unwrap();
+
+        let backup = fs::read_to_string(backup_path(&path)).unwrap();
+        assert_eq!(backup, before);
+        assert_ne!(fs::read_to_string(&path).unwrap(), before);
+    }
+
+    #[test]
+    fn add_redirects_after_prefixed() {
+        let _docs = DocFixtures::new(
+            &[s("Web/API/ExampleOne"), s("Web/API/ExampleTwo")],
+            Locale::EnUs,
+        );
+        let _all_redirects = RedirectFixtures::all_locales_empty();
+        let _redirects = RedirectFixtures::new(&[], Locale::EnUs);
+        let prefixed_path = prefixed_redirects_path(Locale::EnUs, "reference").unwrap();
+
+        let prefixed = add_redirects_with_prefix(
+            Locale::EnUs,
+            &[(
+                s("/en-US/reference/A"),
+                s("/en-US/reference/Web/API/ExampleOne"),
+            )],
+            "reference",
+        );
+        let added = add_redirects(
+            Locale::EnUs,
+            &[(s("/en-US/docs/B"), s("/en-US/docs/Web/API/ExampleTwo"))],
+        );
+        let validated = validate_redirects(Some(&[Locale::EnUs]));
+        let prefixed_redirects = read_redirects_raw(&prefixed_path)
+            .map(|iter| iter.into_iter().collect::<HashMap<_, _>>());
+        fs::remove_file(&prefixed_path).unwrap();
+        fs::remove_file(backup_path(&prefixed_path)).ok();
+
+        prefixed.unwrap();
+        added.unwrap();
+        validated.unwrap();
+        assert_eq!(
+            prefixed_redirects.unwrap(),
+            HashMap::from([(
+                s("/en-US/reference/A"),
+                s("/en-US/reference/Web/API/ExampleOne")
+            )])
+        );
+        let redirects = crate::utils::get_redirects_map(Locale::EnUs);
+        assert_eq!(
+            redirects,
+            HashMap::from([(s("/en-US/docs/B"), s("/en-US/docs/Web/API/ExampleTwo"))])
+        );
+    }
+
+    #[test]
+    fn repair_redirects_remove() {
+        let _docs = DocFixtures::new(&[s("Web/API/ExampleOne")], Locale::EnUs);
+        let pairs = vec![
+            (s("docs/A"), s("docs/Web/API/Gone")),
+            (s("docs/B"), s("docs/Web/API/ExampleOne")),
+        ];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+
+        let repairs = repair_redirects(Locale::EnUs, None).unwrap();
+        assert_eq!(repairs.len(), 1);
+        assert_eq!(repairs[0].new_to, None);
+
+        let redirects = crate::utils::get_redirects_map(Locale::EnUs);
+        assert_eq!(redirects.len(), 1);
+        assert!(!redirects.contains_key("/en-US/docs/A"));
+    }
+
+    #[test]
+    fn rebuild_redirects_messy() {
+        let _docs = DocFixtures::new(
+            &[s("Web/API/ExampleOne"), s("Web/API/ExampleTwo")],
+            Locale::EnUs,
+        );
+        let pairs = vec![
+            (s("docs/Z"), s("docs/Web/API/ExampleTwo")),
+            (s("docs/F"), s("docs/A")),
+            (s("docs/A"), s("docs/B")),
+            (s("docs/C"), s("docs/Web/API/Gone")),
+            (s("docs/B"), s("docs/Web/API/ExampleOne")),
+            (s("docs/D"), s("docs/E")),
+            (s("docs/E"), s("docs/D")),
+            (s("docs/Z"), s("docs/Web/API/ExampleTwo")),
+        ];
+        let _redirects = RedirectFixtures::new(&pairs, Locale::EnUs);
+
+        let report = rebuild_redirects(Locale::EnUs).unwrap();
+        assert_eq!(
+            report,
+            RebuildReport {
+                kept: 4,
+                flattened: 2,
+                duplicates: 1,
+                cyclic: 2,
+                dangling: 1,
+            }
+        );
+        assert_eq!(report.removed(), 4);
+
+        let path = redirects_path(Locale::EnUs).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            concat_strs!(
+                REDIRECT_FILE_HEADER,
+                "/en-US/docs/A\t/en-US/docs/Web/API/ExampleOne\n",
+                "/en-US/docs/B\t/en-US/docs/Web/API/ExampleOne\n",
+                "/en-US/docs/F\t/en-US/docs/Web/API/ExampleOne\n",
+                "/en-US/docs/Z\t/en-US/docs/Web/API/ExampleTwo\n"
+            )
+        );
+        assert!(!tmp_path(&path).exists());
+    }
 }
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use rari_doc::build::SitemapMeta;
+use rari_doc::helpers::subpages::get_sub_pages;
+use rari_doc::pages::page::{PageCategory, PageLike};
+use rari_doc::pages::types::doc::Doc;
+use rari_doc::resolve::build_url;
+use rari_doc::utils::root_for_locale;
+use rari_sitemap::{Sitemap, Url};
+use rari_types::locale::Locale;
+
+use crate::error::ToolError;
+
+/// Generates the XML sitemap of all docs in `locale`, sorted by URL.
+///
+/// Every top-level doc (`Web`, `Learn`, …) is listed with all of its
+/// subpages. The `lastmod` of an entry is the modification time of the doc's
+/// file.
+pub fn generate_sitemap(locale: Locale) -> Result<String, ToolError> {
+    let locale_root = root_for_locale(locale)?.join(locale.as_folder_str());
+    let mut pages = vec![];
+    for entry in fs::read_dir(&locale_root)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let Ok(page) = Doc::page_from_slug_path(Path::new(&entry.file_name()), locale, false)
+        else {
+            continue;
+        };
+        let url = build_url(page.slug(), locale, PageCategory::Doc)?;
+        pages.extend(get_sub_pages(&url, None, Default::default())?);
+        pages.push(page);
+    }
+
+    let mut meta = pages
+        .iter()
+        .map(|page| {
+            let modified = fs::metadata(page.full_path())?.modified()?;
+            Ok(SitemapMeta {
+                url: page.url().into(),
+                modified: Some(DateTime::<Utc>::from(modified).naive_utc()),
+                locale,
+            })
+        })
+        .collect::<Result<Vec<_>, ToolError>>()?;
+    meta.sort_by(|a, b| a.url.cmp(&b.url));
+
+    let sitemap = Sitemap::new(meta.iter().map(Url::from).collect::<Vec<_>>());
+    Ok(String::try_from(&sitemap)?)
+}
+
+// These tests use file system fixtures to simulate content and translated content.
+// The file system is a shared resource, so we force tests to be run serially,
+// to avoid concurrent fixture management issues.
+// Using `file_serial` as a synchronization lock, we should be able to run all tests
+// using the same `key` (here: file_fixtures) to be serialized across modules.
+#[cfg(test)]
+use serial_test::file_serial;
+#[cfg(test)]
+#[file_serial(file_fixtures)]
+mod test {
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    use rari_doc::pages::page::Page;
+    use rari_types::globals::base_url;
+
+    use super::*;
+    use crate::tests::fixtures::docs::DocFixtures;
+
+    #[test]
+    fn test_generate_sitemap() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let urls = [
+            "/en-US/docs/Web",
+            "/en-US/docs/Web/API",
+            "/en-US/docs/Web/API/ExampleOne",
+            "/en-US/docs/Web/API/ExampleOne/SubExampleOne",
+        ];
+        // 2023-11-14T22:13:20Z
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        for url in urls {
+            let page = Page::from_url(url).unwrap();
+            File::options()
+                .write(true)
+                .open(page.full_path())
+                .unwrap()
+                .set_modified(modified)
+                .unwrap();
+        }
+        let sitemap = generate_sitemap(Locale::EnUs).unwrap();
+
+        let urls = urls
+            .iter()
+            .map(|url| {
+                format!(
+                    "<url><loc>{}{url}</loc><lastmod>2023-11-14</lastmod></url>",
+                    base_url()
+                )
+            })
+            .collect::<String>();
+        assert_eq!(
+            sitemap,
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                <urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">{urls}</urlset>"
+            )
+        );
+    }
+}
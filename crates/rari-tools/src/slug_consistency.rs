@@ -0,0 +1,315 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rari_doc::error::DocError;
+use rari_doc::pages::page::{Page, PageCategory, PageReader, PageWriter};
+use rari_doc::pages::types::doc::{Doc, FrontMatter};
+use rari_doc::reader::read_docs_parallel;
+use rari_doc::resolve::{build_url, url_meta_from, UrlMeta};
+use rari_doc::utils::{root_for_locale, split_fm};
+use rari_types::locale::Locale;
+use rari_utils::io::read_to_string;
+
+use crate::error::ToolError;
+use crate::redirects::add_redirects;
+
+/// A doc whose frontmatter slug does not match the folder it lives in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlugMismatch {
+    pub slug: String,
+    /// The folder of the doc, relative to the locale folder.
+    pub folder: PathBuf,
+    /// The folder the slug maps to, relative to the locale folder.
+    pub expected_folder: PathBuf,
+}
+
+/// Only the bits of a doc needed for the check. Reading a full `Doc` fails on
+/// exactly the mismatches we want to report.
+struct SlugEntry {
+    full_path: PathBuf,
+    slug: String,
+}
+
+impl PageReader<SlugEntry> for SlugEntry {
+    fn read(path: impl Into<PathBuf>, _: Option<Locale>) -> Result<SlugEntry, DocError> {
+        let full_path = path.into();
+        let raw = read_to_string(&full_path)?;
+        let (fm, _) = split_fm(&raw);
+        let fm = fm.ok_or(DocError::NoFrontmatter)?;
+        let FrontMatter { slug, .. } = serde_yaml_ng::from_str(fm)?;
+        Ok(SlugEntry { full_path, slug })
+    }
+}
+
+impl SlugEntry {
+    /// The folder of the doc, relative to `locale_root`.
+    fn folder(&self, locale_root: &Path) -> PathBuf {
+        self.full_path
+            .strip_prefix(locale_root)
+            .ok()
+            .and_then(Path::parent)
+            .unwrap_or(Path::new(""))
+            .to_path_buf()
+    }
+}
+
+/// The folder `slug` maps to, relative to the locale folder.
+fn slug_folder(slug: &str, locale: Locale) -> Result<PathBuf, ToolError> {
+    let url = build_url(slug, locale, PageCategory::Doc)?;
+    let UrlMeta { folder_path, .. } = url_meta_from(&url)?;
+    Ok(folder_path)
+}
+
+fn read_slug_entries(locale: Locale) -> Result<(PathBuf, Vec<SlugEntry>), ToolError> {
+    let locale_root = root_for_locale(locale)?.join(locale.as_folder_str());
+    let entries = read_docs_parallel::<SlugEntry, SlugEntry>(&[&locale_root], None)?;
+    Ok((locale_root, entries))
+}
+
+/// Compares the frontmatter slug of every doc in `locale` with the folder the
+/// doc lives in and returns all mismatches, sorted by slug. Mismatches are
+/// typically left behind by interrupted moves.
+pub fn verify_slug_consistency(locale: Locale) -> Result<Vec<SlugMismatch>, ToolError> {
+    let (locale_root, entries) = read_slug_entries(locale)?;
+
+    let mut mismatches = entries
+        .into_iter()
+        .map(|entry| {
+            let expected_folder = slug_folder(&entry.slug, locale)?;
+            let folder = entry.folder(&locale_root);
+            Ok::<_, ToolError>((folder != expected_folder).then_some(SlugMismatch {
+                slug: entry.slug,
+                folder,
+                expected_folder,
+            }))
+        })
+        .filter_map(Result::transpose)
+        .collect::<Result<Vec<_>, _>>()?;
+    mismatches.sort_by(|a, b| a.slug.cmp(&b.slug));
+    Ok(mismatches)
+}
+
+/// Returns every slug in `locale` that more than one doc claims, with the
+/// files of those docs relative to the locale folder. Slugs are compared
+/// lowercased, as URLs are resolved case-insensitively. Both lists are sorted.
+pub fn find_duplicate_slugs(locale: Locale) -> Result<Vec<(String, Vec<PathBuf>)>, ToolError> {
+    let (locale_root, entries) = read_slug_entries(locale)?;
+
+    let mut by_slug: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for entry in entries {
+        let file = entry
+            .full_path
+            .strip_prefix(&locale_root)
+            .unwrap_or(&entry.full_path)
+            .to_path_buf();
+        by_slug
+            .entry(entry.slug.to_lowercase())
+            .or_default()
+            .push(file);
+    }
+    let mut duplicates = by_slug
+        .into_iter()
+        .filter(|(_, files)| files.len() > 1)
+        .map(|(slug, mut files)| {
+            files.sort();
+            (slug, files)
+        })
+        .collect::<Vec<_>>();
+    duplicates.sort();
+    Ok(duplicates)
+}
+
+/// Rewrites the frontmatter slug of every doc in `locale` that is not in
+/// canonical form and updates the redirects accordingly. Returns the
+/// `(old_slug, new_slug)` pairs, sorted by old slug. With `dry_run` set,
+/// nothing is written.
+///
+/// The canonical slug of a doc is the canonical slug of the doc in the parent
+/// folder followed by the last segment of its own slug, so the casing of a
+/// prefix follows the docs it names. Docs whose slug does not map to their
+/// folder at all are left alone, [`verify_slug_consistency`] reports those.
+///
+/// As with moves, a change in casing only fixes the casing of existing
+/// redirect targets, other changes add a redirect from the old URL.
+pub fn canonicalize_slugs(
+    locale: Locale,
+    dry_run: bool,
+) -> Result<Vec<(String, String)>, ToolError> {
+    let (locale_root, mut entries) = read_slug_entries(locale)?;
+    // Parents first, so their canonical slug is known for their children.
+    entries.sort_by_key(|entry| entry.full_path.components().count());
+
+    let mut canonical = HashMap::new();
+    let mut changes = vec![];
+    for entry in entries {
+        let folder = entry.folder(&locale_root);
+        if folder != slug_folder(&entry.slug, locale)? {
+            continue;
+        }
+        let parent_slug = folder
+            .parent()
+            .and_then(|parent| canonical.get(parent))
+            .map(String::as_str);
+        let canonical_slug = match (parent_slug, entry.slug.rsplit_once('/')) {
+            (Some(parent_slug), Some((_, name))) => {
+                let slug = format!("{parent_slug}/{name}");
+                if folder == slug_folder(&slug, locale)? {
+                    slug
+                } else {
+                    entry.slug.clone()
+                }
+            }
+            _ => entry.slug.clone(),
+        };
+        if canonical_slug != entry.slug {
+            changes.push((entry.full_path, entry.slug, canonical_slug.clone()));
+        }
+        canonical.insert(folder, canonical_slug);
+    }
+    changes.sort_by(|a, b| a.1.cmp(&b.1));
+
+    if !dry_run && !changes.is_empty() {
+        for (full_path, _, new_slug) in &changes {
+            if let Page::Doc(mut doc) = Doc::read(full_path, Some(locale))? {
+                let doc = Arc::make_mut(&mut doc);
+                doc.meta.slug = new_slug.clone();
+                doc.write()?;
+            }
+        }
+        let url_pairs = changes
+            .iter()
+            .map(|(_, old_slug, new_slug)| {
+                Ok((
+                    build_url(old_slug, locale, PageCategory::Doc)?,
+                    build_url(new_slug, locale, PageCategory::Doc)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, ToolError>>()?;
+        add_redirects(locale, &url_pairs)?;
+    }
+
+    Ok(changes
+        .into_iter()
+        .map(|(_, old_slug, new_slug)| (old_slug, new_slug))
+        .collect())
+}
+
+// These tests use file system fixtures to simulate content and translated content.
+// The file system is a shared resource, so we force tests to be run serially,
+// to avoid concurrent fixture management issues.
+// Using `file_serial` as a synchronization lock, we should be able to run all tests
+// using the same `key` (here: file_fixtures) to be serialized across modules.
+#[cfg(test)]
+use serial_test::file_serial;
+#[cfg(test)]
+#[file_serial(file_fixtures)]
+mod test {
+    use super::*;
+    use crate::tests::fixtures::docs::DocFixtures;
+    use crate::tests::fixtures::redirects::RedirectFixtures;
+    use crate::utils::get_redirects_map;
+
+    #[test]
+    fn test_verify_slug_consistency() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        assert_eq!(verify_slug_consistency(Locale::EnUs).unwrap(), vec![]);
+
+        // Simulate an interrupted move: the slug was updated, the folder was not.
+        let path = root_for_locale(Locale::EnUs)
+            .unwrap()
+            .join("en-us/web/api/exampleone/subexampleone/index.md");
+        let content = std::fs::read_to_string(&path).unwrap().replace(
+            "slug: Web/API/ExampleOne/SubExampleOne",
+            "slug: Web/API/ExampleOne/SubExampleMoved",
+        );
+        std::fs::write(&path, content).unwrap();
+
+        assert_eq!(
+            verify_slug_consistency(Locale::EnUs).unwrap(),
+            vec![SlugMismatch {
+                slug: "Web/API/ExampleOne/SubExampleMoved".to_string(),
+                folder: PathBuf::from("web/api/exampleone/subexampleone"),
+                expected_folder: PathBuf::from("web/api/exampleone/subexamplemoved"),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_find_duplicate_slugs() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+            "Web/API/ExampleTwo".to_string(),
+        ];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        assert_eq!(find_duplicate_slugs(Locale::EnUs).unwrap(), vec![]);
+
+        // A copied doc whose slug was not updated, differing only in case.
+        let path = root_for_locale(Locale::EnUs)
+            .unwrap()
+            .join("en-us/web/api/exampletwo/index.md");
+        let content = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("slug: Web/API/ExampleTwo", "slug: Web/API/exampleone");
+        std::fs::write(&path, content).unwrap();
+
+        assert_eq!(
+            find_duplicate_slugs(Locale::EnUs).unwrap(),
+            vec![(
+                "web/api/exampleone".to_string(),
+                vec![
+                    PathBuf::from("web/api/exampleone/index.md"),
+                    PathBuf::from("web/api/exampletwo/index.md"),
+                ]
+            )]
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_slugs() {
+        let slugs = vec![
+            "Web/API/ExampleOne".to_string(),
+            "Web/API/ExampleOne/SubExampleOne".to_string(),
+        ];
+        let redirects = vec![(
+            "docs/Web/API/OldExample".to_string(),
+            "docs/Web/api/ExampleOne".to_string(),
+        )];
+        let _docs = DocFixtures::new(&slugs, Locale::EnUs);
+        let _redirects = RedirectFixtures::new(&redirects, Locale::EnUs);
+        assert_eq!(canonicalize_slugs(Locale::EnUs, false).unwrap(), vec![]);
+
+        // Drift in casing, the folder stays the same.
+        let path = root_for_locale(Locale::EnUs)
+            .unwrap()
+            .join("en-us/web/api/exampleone/index.md");
+        let content = std::fs::read_to_string(&path)
+            .unwrap()
+            .replace("slug: Web/API/ExampleOne", "slug: Web/api/ExampleOne");
+        std::fs::write(&path, &content).unwrap();
+
+        let expected = vec![(
+            "Web/api/ExampleOne".to_string(),
+            "Web/API/ExampleOne".to_string(),
+        )];
+        assert_eq!(canonicalize_slugs(Locale::EnUs, true).unwrap(), expected);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), content);
+
+        assert_eq!(canonicalize_slugs(Locale::EnUs, false).unwrap(), expected);
+        assert!(std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("slug: Web/API/ExampleOne\n"));
+        assert_eq!(
+            get_redirects_map(Locale::EnUs)
+                .get("/en-US/docs/Web/API/OldExample")
+                .unwrap(),
+            "/en-US/docs/Web/API/ExampleOne"
+        );
+        assert_eq!(canonicalize_slugs(Locale::EnUs, false).unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,79 @@
+use std::path::PathBuf;
+
+use rari_doc::pages::page::PageCategory;
+use rari_doc::resolve::{build_url, url_to_folder_path};
+use rari_doc::templ::api::RariApi;
+use rari_types::locale::Locale;
+
+use crate::error::ToolError;
+
+/// What a title turns into when used for a new doc.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlugPreview {
+    /// The slug segment derived from the title.
+    pub segment: String,
+    /// The full slug, including the parent slug if given.
+    pub slug: String,
+    pub url: String,
+    /// The folder of the doc, relative to the locale folder.
+    pub folder: PathBuf,
+}
+
+/// Returns the slug segment for `title`. This is the same normalization used
+/// for heading ids: rejected punctuation is dropped, the title is lowercased
+/// and whitespace becomes `_`.
+pub fn slugify(title: &str) -> String {
+    RariApi::anchorize(title).into_owned()
+}
+
+/// Previews the slug, URL and folder a doc titled `title` would get below
+/// `parent` in `locale`.
+pub fn slugify_preview(
+    title: &str,
+    parent: Option<&str>,
+    locale: Locale,
+) -> Result<SlugPreview, ToolError> {
+    let segment = slugify(title);
+    let slug = match parent.map(|parent| parent.trim_matches('/')) {
+        Some(parent) if !parent.is_empty() => format!("{parent}/{segment}"),
+        _ => segment.clone(),
+    };
+    let url = build_url(&slug, locale, PageCategory::Doc)?;
+    let folder = url_to_folder_path(&slug);
+    Ok(SlugPreview {
+        segment,
+        slug,
+        url,
+        folder,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Using  the Fetch API"), "using_the_fetch_api");
+        assert_eq!(
+            slugify("What's new? (2024: <part> #1)"),
+            "whats_new_2024_part_1"
+        );
+        assert_eq!(slugify("Éléments  HTML"), "éléments_html");
+        assert_eq!(slugify("???"), "sect");
+    }
+
+    #[test]
+    fn test_slugify_preview() {
+        let preview = slugify_preview("Fetch API", Some("Web/API/"), Locale::Fr).unwrap();
+        assert_eq!(
+            preview,
+            SlugPreview {
+                segment: "fetch_api".to_string(),
+                slug: "Web/API/fetch_api".to_string(),
+                url: "/fr/docs/Web/API/fetch_api".to_string(),
+                folder: PathBuf::from("web/api/fetch_api"),
+            }
+        );
+    }
+}
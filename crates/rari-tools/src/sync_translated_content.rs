@@ -1,6 +1,5 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::ffi::OsStr;
 
 use console::Style;
 use rari_doc::pages::page::{Page, PageCategory, PageLike, PageWriter};
@@ -12,7 +11,7 @@ use rari_utils::concat_strs;
 use sha2::{Digest, Sha256};
 
 use crate::error::ToolError;
-use crate::git::exec_git_with_test_fallback;
+use crate::git::git_mv;
 use crate::redirects::{add_redirects, fix_redirects};
 use crate::utils::{get_redirects_map, read_all_doc_pages};
 use crate::wikihistory::update_wiki_history;
@@ -313,21 +312,11 @@ fn write_and_move_doc(doc: &Doc, target_slug: &str) -> Result<(), ToolError> {
     new_doc.write()?;
 
     // Move the file with git
-    let output = exec_git_with_test_fallback(
-        &[
-            OsStr::new("mv"),
-            source_path.as_os_str(),
-            target_directory.as_os_str(),
-        ],
+    git_mv(
+        source_path,
+        &target_directory,
         root_for_locale(doc.locale())?,
-    );
-
-    if !output.status.success() {
-        return Err(ToolError::GitError(format!(
-            "Failed to move files: {}",
-            String::from_utf8_lossy(&output.stderr)
-        )));
-    }
+    )?;
 
     // If the source directory is empty, remove it with the fs api.
     let source_directory = doc.full_path().parent().unwrap();
@@ -68,5 +68,6 @@ impl Drop for RedirectFixtures {
         }
 
         fs::remove_file(&self.path).ok();
+        fs::remove_file(crate::redirects::backup_path(&self.path)).ok();
     }
 }